@@ -0,0 +1,6 @@
+pub mod batch;
+pub mod config;
+pub mod output;
+pub mod repl;
+pub mod store;
+pub mod watch;
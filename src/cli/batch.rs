@@ -0,0 +1,399 @@
+use std::{
+    collections::HashSet,
+    fs::{self, DirEntry},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Error, Result, bail};
+use json_replacer::Translated;
+use serde::Serialize;
+
+/// A file to translate, with its path below the directory or glob base it
+/// was found in, used to mirror it in the output directory.
+pub struct BatchFile {
+    pub path: PathBuf,
+    pub relative: PathBuf,
+}
+
+/// Translations of all files of a batch.
+#[derive(Serialize)]
+pub struct BatchReport<'r> {
+    pub files: usize,
+    pub failed: usize,
+    pub results: Vec<FileReport<'r>>,
+}
+
+/// Translation or error of one file.
+#[derive(Serialize)]
+pub struct FileReport<'r> {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Translated<'r>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<'r> FileReport<'r> {
+    pub fn translated(path: String, translated: Translated<'r>) -> Self {
+        FileReport {
+            path,
+            kind: Some(translated.kind().to_string()),
+            result: Some(translated),
+            error: None,
+        }
+    }
+
+    pub fn failed(path: String, error: &Error) -> Self {
+        FileReport {
+            path,
+            kind: None,
+            result: None,
+            error: Some(format!("{error:#}")),
+        }
+    }
+}
+
+/// Files of the inputs: files as given, json files of directories and their
+/// subdirectories and files matching glob patterns with `*`, `?` and `**`.
+pub fn collect(inputs: &[String]) -> Result<Vec<BatchFile>> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        let found = if is_glob(input) {
+            glob(input)?
+        } else if path.is_dir() {
+            let mut paths = Vec::new();
+            walk(path, &mut paths)?;
+
+            paths
+                .into_iter()
+                .filter(|file| {
+                    file.extension()
+                        .is_some_and(|extension| extension == "json")
+                })
+                .map(|file| BatchFile {
+                    relative: file.strip_prefix(path).unwrap_or(&file).to_path_buf(),
+                    path: file,
+                })
+                .collect()
+        } else if path.is_file() {
+            vec![BatchFile {
+                path: path.to_path_buf(),
+                relative: PathBuf::from(path.file_name().context("input has no file name")?),
+            }]
+        } else {
+            bail!("no such file or directory: {input}");
+        };
+
+        if found.is_empty() {
+            bail!("no files match {input}");
+        }
+
+        // The same file given by another path, e.g. `./a.json` and `a.json`,
+        // is only translated once.
+        files.extend(found.into_iter().filter(|file| {
+            seen.insert(fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone()))
+        }));
+    }
+
+    Ok(files)
+}
+
+/// Drop the files below `dir`, e.g. translations of an earlier run written
+/// to an output directory inside an input directory.
+pub fn skip_dir(files: &mut Vec<BatchFile>, dir: &Path) {
+    // A directory that doesn't exist yet holds no files.
+    let Ok(dir) = fs::canonicalize(dir) else {
+        return;
+    };
+
+    files.retain(|file| fs::canonicalize(&file.path).map_or(true, |path| !path.starts_with(&dir)));
+}
+
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?'])
+}
+
+/// Entries of `dir` sorted by name, hidden files and directories skipped.
+fn entries(dir: &Path) -> Result<Vec<DirEntry>> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter(|entry| {
+            entry.as_ref().map_or(true, |entry| {
+                !entry.file_name().to_string_lossy().starts_with('.')
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    Ok(entries)
+}
+
+/// Files below `dir` sorted by path, hidden files and directories skipped.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in entries(dir)? {
+        let path = entry.path();
+
+        if entry.file_type()?.is_dir() {
+            walk(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Files matching a glob pattern, relative to the directory before its first wildcard.
+fn glob(pattern: &str) -> Result<Vec<BatchFile>> {
+    let components: Vec<&str> = pattern.split('/').collect();
+    let literal = components
+        .iter()
+        .position(|component| is_glob(component))
+        .unwrap_or(components.len());
+
+    let base = match components[..literal].join("/") {
+        base if !base.is_empty() => PathBuf::from(base),
+        _ if pattern.starts_with('/') => PathBuf::from("/"),
+        _ => PathBuf::from("."),
+    };
+    let mut pattern: Vec<&str> = components[literal..]
+        .iter()
+        .copied()
+        .filter(|component| !component.is_empty())
+        .collect();
+    // Repeated `**` match the same paths as one.
+    pattern.dedup_by(|a, b| *a == "**" && *b == "**");
+
+    if !base.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    walk_pattern(&base, &pattern, &mut paths)?;
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .map(|path| BatchFile {
+            relative: path.strip_prefix(&base).unwrap_or(&path).to_path_buf(),
+            path,
+        })
+        .collect())
+}
+
+/// Files below `dir` matching pattern components, `**` matching any number
+/// of them. Directories are only entered as deep as the pattern reaches.
+fn walk_pattern(dir: &Path, pattern: &[&str], files: &mut Vec<PathBuf>) -> Result<()> {
+    let Some((&component, rest)) = pattern.split_first() else {
+        return Ok(());
+    };
+
+    if component == "**" {
+        // `**` matching no directory.
+        walk_pattern(dir, rest, files)?;
+    }
+
+    for entry in entries(dir)? {
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+
+        if component == "**" {
+            if is_dir {
+                walk_pattern(&path, pattern, files)?;
+            } else if rest.is_empty() {
+                files.push(path);
+            }
+        } else if matches_name(component, &entry.file_name().to_string_lossy()) {
+            if rest.is_empty() {
+                if !is_dir {
+                    files.push(path);
+                }
+            } else if is_dir {
+                walk_pattern(&path, rest, files)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a name matches a pattern of `*`, any run of characters, and `?`, one character.
+fn matches_name(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Last `*` and the name position it was tried at, to backtrack to.
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    /// Empty directory of a test below the system temp directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("json-replacer-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn relatives(files: &[BatchFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.relative.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn matches_names() {
+        assert!(matches_name("*.json", "response.json"));
+        assert!(matches_name("*.json", ".json"));
+        assert!(!matches_name("*.json", "response.jsonl"));
+        assert!(matches_name("?.json", "a.json"));
+        assert!(!matches_name("?.json", "ab.json"));
+        assert!(matches_name("r*s*.json", "responses.json"));
+        // The first `*` has to give back what it matched.
+        assert!(matches_name("*a*b.json", "aab_ab.json"));
+        assert!(!matches_name("*a*b.json", "aab_a.json"));
+        assert!(matches_name("*", ""));
+        assert!(!matches_name("?", ""));
+    }
+
+    #[test]
+    fn globs_only_as_deep_as_the_pattern() {
+        let root = temp_dir("batch-glob");
+        fs::create_dir_all(root.join("a").join("b").join("c")).unwrap();
+
+        for file in ["x.json", "a/y.json", "a/b/c/z.json", "a/b/c/z.txt"] {
+            fs::write(root.join(file), "{}").unwrap();
+        }
+
+        let pattern = |pattern: &str| format!("{}/{pattern}", root.display());
+
+        assert_eq!(relatives(&glob(&pattern("*.json")).unwrap()), ["x.json"]);
+        assert_eq!(
+            relatives(&glob(&pattern("*/*.json")).unwrap()),
+            ["a/y.json"]
+        );
+        assert_eq!(
+            relatives(&glob(&pattern("**/*.json")).unwrap()),
+            ["a/b/c/z.json", "a/y.json", "x.json"]
+        );
+        assert_eq!(
+            relatives(&glob(&pattern("a/**/c/*.json")).unwrap()),
+            ["b/c/z.json"]
+        );
+        assert_eq!(
+            relatives(&glob(&pattern("a/**/**/c/*")).unwrap()),
+            ["b/c/z.json", "b/c/z.txt"]
+        );
+        assert_eq!(relatives(&glob(&pattern("a/**")).unwrap()).len(), 3);
+        assert!(glob(&pattern("*/c/*.json")).unwrap().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn collects_inputs() {
+        let root = temp_dir("batch-collect");
+        fs::create_dir_all(root.join("captures").join("nested")).unwrap();
+        fs::create_dir_all(root.join("captures").join(".hidden")).unwrap();
+
+        for file in [
+            "captures/a.json",
+            "captures/notes.txt",
+            "captures/nested/b.json",
+            "captures/.hidden/c.json",
+            "single.json",
+        ] {
+            fs::write(root.join(file), "{}").unwrap();
+        }
+
+        let dir = root.join("captures").display().to_string();
+        let single = root.join("single.json").display().to_string();
+        let pattern = format!("{}/**/*.json", root.display());
+
+        let files = collect(&[dir.clone(), single.clone()]).unwrap();
+
+        assert_eq!(
+            relatives(&files),
+            ["a.json", "nested/b.json", "single.json"]
+        );
+
+        // Files found again by a later input are only listed once.
+        let files = collect(&[dir, pattern]).unwrap();
+
+        assert_eq!(
+            relatives(&files),
+            ["a.json", "nested/b.json", "single.json"]
+        );
+        assert_eq!(files[2].path, root.join("single.json"));
+
+        // So are files given by another path.
+        let dotted = root.join("captures").join("..").join("single.json");
+        let files = collect(&[single, dotted.display().to_string()]).unwrap();
+
+        assert_eq!(files.len(), 1);
+
+        let missing = root.join("missing.json").display().to_string();
+        assert!(collect(&[missing]).is_err());
+
+        let unmatched = format!("{}/*.yaml", root.display());
+        assert!(collect(&[unmatched]).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn skips_output_directory() {
+        let root = temp_dir("batch-skip");
+        fs::create_dir_all(root.join("translated")).unwrap();
+        fs::write(root.join("a.json"), "{}").unwrap();
+        fs::write(root.join("translated").join("a.json"), "{}").unwrap();
+
+        let mut files = collect(&[root.display().to_string()]).unwrap();
+        assert_eq!(files.len(), 2);
+
+        skip_dir(&mut files, &root.join("translated"));
+
+        assert_eq!(relatives(&files), ["a.json"]);
+
+        skip_dir(&mut files, &root.join("missing"));
+
+        assert_eq!(files.len(), 1);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use json_replacer::{AttributeOrder, KeyStyle, Options, Timezone};
+use serde::{Deserialize, Serialize};
+
+/// Name of the project config, searched upward from the working directory.
+const PROJECT_CONFIG: &str = ".json-replacer.toml";
+
+#[derive(ValueEnum, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Rust debug output
+    #[default]
+    Debug,
+    /// Pretty printed json
+    Json,
+}
+
+/// Settings read from the user config, then the project config, then flags.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Paths of the reference files, relative to the config file. Attributes
+    /// of earlier files win on duplicate ids.
+    pub references: Option<Vec<PathBuf>>,
+    /// Name of the stored reference used when none is given.
+    pub default_ref: Option<String>,
+    /// Output format: debug or json.
+    pub format: Option<OutputFormat>,
+    /// Key style: label, name, name-type, id or label-id.
+    pub key_style: Option<KeyStyle>,
+    /// Order of attributes: key, reference or id.
+    pub order: Option<AttributeOrder>,
+    /// Fail on unknown attributes and picklist options.
+    pub strict: Option<bool>,
+    /// Attributes whose values are replaced, by id, name, label or `type:<data type>`.
+    pub redact: Option<Vec<String>>,
+    /// Format values by data type for display.
+    pub display: Option<bool>,
+    /// Currency code of amounts, e.g. EUR.
+    pub currency: Option<String>,
+    /// Timezone of datetimes, an IANA name or an offset like +08:00.
+    pub timezone: Option<Timezone>,
+    /// Show changed values next to their original value.
+    pub keep_original: Option<bool>,
+    /// Value templates by attribute selector, `{value}` is the translated value,
+    /// see [`Options::templates`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Settings of `other` take precedence, redaction rules of both apply.
+    pub fn merge(mut self, other: Config) -> Config {
+        self.templates.extend(other.templates);
+
+        let redact = match (self.redact, other.redact) {
+            (Some(mut redact), Some(other)) => {
+                for selector in other {
+                    if !redact.contains(&selector) {
+                        redact.push(selector);
+                    }
+                }
+
+                Some(redact)
+            }
+            (redact, other) => other.or(redact),
+        };
+
+        Config {
+            references: other.references.or(self.references),
+            default_ref: other.default_ref.or(self.default_ref),
+            format: other.format.or(self.format),
+            key_style: other.key_style.or(self.key_style),
+            order: other.order.or(self.order),
+            strict: other.strict.or(self.strict),
+            redact,
+            display: other.display.or(self.display),
+            currency: other.currency.or(self.currency),
+            timezone: other.timezone.or(self.timezone),
+            keep_original: other.keep_original.or(self.keep_original),
+            templates: self.templates,
+        }
+    }
+
+    pub fn options(&self) -> Options {
+        Options {
+            key_style: self.key_style.unwrap_or_default(),
+            order: self.order.unwrap_or_default(),
+            strict: self.strict.unwrap_or_default(),
+            redact: self.redact.clone().unwrap_or_default(),
+            templates: self
+                .templates
+                .iter()
+                .map(|(selector, template)| (selector.clone(), template.clone()))
+                .collect(),
+            display: self.display.unwrap_or_default(),
+            currency: self.currency.clone(),
+            timezone: self.timezone.clone(),
+            keep_original: self.keep_original.unwrap_or_default(),
+            ..Options::default()
+        }
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("json-replacer"))
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Nearest project config in the working directory or its ancestors.
+pub fn project_config_path() -> Option<PathBuf> {
+    find_project_config(&env::current_dir().ok()?)
+}
+
+fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|path| path.is_file())
+}
+
+fn read(path: &Path) -> Result<Config> {
+    let content = fs::read_to_string(path)?;
+    let mut config: Config =
+        toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))?;
+
+    if let (Some(references), Some(dir)) = (&mut config.references, path.parent()) {
+        for reference in references {
+            *reference = dir.join(&reference);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Merged user and project config, with the paths that were read.
+pub fn load() -> Result<(Config, Vec<PathBuf>)> {
+    let user_config = user_config_path().filter(|path| path.is_file());
+
+    merge_files([user_config, project_config_path()].into_iter().flatten())
+}
+
+/// Config files merged in order, later files taking precedence.
+fn merge_files(paths: impl IntoIterator<Item = PathBuf>) -> Result<(Config, Vec<PathBuf>)> {
+    let mut config = Config::default();
+    let mut sources = Vec::new();
+
+    for path in paths {
+        config = config.merge(read(&path)?);
+        sources.push(path);
+    }
+
+    Ok((config, sources))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    /// Empty directory of a test below the system temp directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("json-replacer-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_nearest_project_config() {
+        let root = temp_dir("project-config");
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_CONFIG), "strict = true").unwrap();
+
+        assert_eq!(
+            find_project_config(&nested),
+            Some(root.join(PROJECT_CONFIG))
+        );
+
+        fs::write(root.join("a").join(PROJECT_CONFIG), "strict = false").unwrap();
+
+        assert_eq!(
+            find_project_config(&nested),
+            Some(root.join("a").join(PROJECT_CONFIG))
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn project_config_takes_precedence() {
+        let root = temp_dir("merge-config");
+        let user = root.join("config.toml");
+        let project = root.join("project").join(PROJECT_CONFIG);
+        fs::create_dir_all(project.parent().unwrap()).unwrap();
+
+        fs::write(
+            &user,
+            "format = \"json\"\nstrict = true\n[templates]\nAmount = \"{value} USD\"",
+        )
+        .unwrap();
+        fs::write(
+            &project,
+            "references = [\"oa.json\", \"../shared.json\"]\nstrict = false\n[templates]\nTotal = \"{value} EUR\"",
+        )
+        .unwrap();
+
+        let (config, sources) = merge_files([user.clone(), project.clone()]).unwrap();
+
+        assert_eq!(sources, vec![user, project]);
+        assert!(matches!(config.format, Some(OutputFormat::Json)));
+        assert_eq!(config.strict, Some(false));
+        assert_eq!(
+            config.references,
+            Some(vec![
+                root.join("project").join("oa.json"),
+                root.join("project").join("../shared.json")
+            ])
+        );
+        assert_eq!(config.templates.len(), 2);
+
+        // Flags are merged last.
+        let config = config.merge(Config {
+            strict: Some(true),
+            ..Config::default()
+        });
+
+        assert_eq!(config.strict, Some(true));
+        assert!(config.options().strict);
+
+        // Redaction rules are added to, not replaced.
+        let config = Config {
+            redact: Some(vec!["Email".to_string()]),
+            ..Config::default()
+        }
+        .merge(Config {
+            redact: Some(vec!["Phone".to_string(), "Email".to_string()]),
+            ..Config::default()
+        });
+
+        assert_eq!(config.options().redact, ["Email", "Phone"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let root = temp_dir("unknown-config");
+        let path = root.join(PROJECT_CONFIG);
+        fs::write(&path, "stritc = true").unwrap();
+
+        assert!(merge_files([path]).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+
+/// Create a file to write results to, an existing file is only replaced with `force`.
+pub fn create(path: &Path, force: bool) -> Result<File> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = OpenOptions::new();
+
+    if force {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+
+    options.open(path).map_err(|error| match error.kind() {
+        ErrorKind::AlreadyExists => anyhow!(
+            "{} already exists, pass --force to overwrite it",
+            path.display()
+        ),
+        _ => anyhow!(error).context(format!("failed to write {}", path.display())),
+    })
+}
+
+pub fn write(path: &Path, content: &str, force: bool) -> Result<()> {
+    create(path, force)?
+        .write_all(content.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Path next to `path` with its `.json` extension replaced by `suffix`, so
+/// `response.json` becomes `response.translated.json`.
+pub fn companion(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name.strip_suffix(".json").unwrap_or(&name);
+
+    path.with_file_name(format!("{stem}{suffix}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    #[test]
+    fn companion_replaces_json_extension() {
+        assert_eq!(
+            companion(Path::new("captures/response.json"), ".translated.json"),
+            PathBuf::from("captures/response.translated.json")
+        );
+        assert_eq!(
+            companion(Path::new("response"), ".translated.json"),
+            PathBuf::from("response.translated.json")
+        );
+        assert_eq!(
+            companion(Path::new("response.txt"), ".translated.json"),
+            PathBuf::from("response.txt.translated.json")
+        );
+    }
+
+    #[test]
+    fn create_refuses_existing_files() {
+        let dir = env::temp_dir().join(format!("json-replacer-output-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("nested").join("result.json");
+
+        write(&path, "first", false).unwrap();
+
+        let error = write(&path, "second", false).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .ends_with("already exists, pass --force to overwrite it")
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+        write(&path, "third", true).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,395 @@
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufRead, IsTerminal, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Result, bail};
+use json_replacer::{InputKind, SchemaFilter, SchemaFormat, Translator, render_schema};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{config::OutputFormat, store};
+
+/// Entries kept in the history file.
+const HISTORY_SIZE: usize = 1000;
+
+const HELP: &str = "\
+Paste a payload, response or entity to translate it, its kind is detected.
+
+  payload|response|entity <json>  translate json as this kind
+  lookup <query>                  find attributes and options by id, name or label
+  query <search query>            payload of a query such as (AND (equal Status \"Open\"))
+  schema [name | type:<type>]     list attributes, by name or label or by data type
+  history                         list earlier commands, run one again with !<n> or !!
+  help                            show this help
+  quit                            leave, as does Ctrl-D";
+
+/// Interactive translation over a loaded reference.
+pub struct Repl {
+    translator: Translator,
+    format: OutputFormat,
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+}
+
+impl Repl {
+    pub fn new(translator: Translator, format: OutputFormat) -> Self {
+        let history_path = store::data_dir().ok().map(|dir| dir.join("history"));
+        let history = history_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|history| {
+                history
+                    .lines()
+                    .filter(|entry| !is_document(entry))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Repl {
+            translator,
+            format,
+            history,
+            history_path,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        let stdin = io::stdin();
+        let interactive = stdin.is_terminal();
+        let mut lines = stdin.lock().lines();
+
+        // Piped entries are scripts, not worth remembering.
+        if !interactive {
+            self.history_path = None;
+        }
+
+        if interactive {
+            println!(
+                "{} attributes loaded, type help for the commands",
+                self.translator.reference().len()
+            );
+        }
+
+        while let Some(entry) = read_entry(&mut lines, interactive)? {
+            let entry = match self.expand(entry.trim()) {
+                Ok(entry) if entry.is_empty() => continue,
+                Ok(entry) => entry,
+                Err(error) => {
+                    eprintln!("Error: {error:#}");
+                    continue;
+                }
+            };
+
+            if matches!(entry.as_str(), "quit" | "exit") {
+                break;
+            }
+
+            self.remember(&entry);
+            self.save_history();
+
+            if let Err(error) = self.execute(&entry) {
+                eprintln!("Error: {error:#}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Entry of the history for `!!` and `!<n>`, other entries as they are.
+    fn expand(&self, entry: &str) -> Result<String> {
+        let Some(index) = entry.strip_prefix('!') else {
+            return Ok(entry.to_string());
+        };
+
+        let index = match index {
+            "!" => self.history.len(),
+            index => match index.parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => bail!("expected !! or !<n>, see history"),
+            },
+        };
+
+        match index
+            .checked_sub(1)
+            .and_then(|index| self.history.get(index))
+        {
+            Some(entry) => {
+                println!("{entry}");
+                Ok(entry.clone())
+            }
+            None => bail!("no history entry {index}"),
+        }
+    }
+
+    /// Add a command to the history, on one line. Pasted documents are left
+    /// out, they may hold customer data and would flood the history.
+    fn remember(&mut self, entry: &str) {
+        if is_document(entry) {
+            return;
+        }
+
+        let entry = entry
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        if self.history.last() != Some(&entry) {
+            self.history.push(entry);
+        }
+    }
+
+    /// Keep the latest entries for later sessions, readable only by the user
+    /// and failing silently as the history is a convenience.
+    fn save_history(&self) {
+        let Some(path) = &self.history_path else {
+            return;
+        };
+
+        let start = self.history.len().saturating_sub(HISTORY_SIZE);
+        let history = self.history[start..].join("\n") + "\n";
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let _ = options
+            .open(path)
+            .and_then(|mut file| file.write_all(history.as_bytes()));
+    }
+
+    fn execute(&self, entry: &str) -> Result<()> {
+        let (command, argument) = entry
+            .split_once(char::is_whitespace)
+            .map_or((entry, ""), |(command, argument)| {
+                (command, argument.trim())
+            });
+
+        match command {
+            "help" => println!("{HELP}"),
+            "history" => {
+                for (index, entry) in self.history.iter().enumerate() {
+                    println!("{:>4}  {entry}", index + 1);
+                }
+            }
+            "lookup" => {
+                if argument.is_empty() {
+                    bail!("usage: lookup <query>");
+                }
+
+                let matches = self.translator.lookup(argument, 10);
+
+                if matches.is_empty() {
+                    println!("No matches for {argument}");
+                }
+
+                for lookup_match in matches {
+                    println!("{lookup_match}");
+                }
+            }
+            "query" => {
+                if argument.is_empty() {
+                    bail!("usage: query <search query>");
+                }
+
+                let payload = self.translator.query_payload(argument)?;
+                println!("{}", serde_json::to_string_pretty(&payload)?);
+            }
+            "schema" => {
+                let filter = match argument.strip_prefix("type:") {
+                    Some(data_type) => SchemaFilter {
+                        data_type: Some(data_type.trim().to_string()),
+                        name: None,
+                    },
+                    None => SchemaFilter {
+                        data_type: None,
+                        name: (!argument.is_empty()).then(|| argument.to_string()),
+                    },
+                };
+
+                println!(
+                    "{}",
+                    render_schema(self.translator.reference(), &filter, SchemaFormat::Table)?
+                );
+            }
+            "payload" | "response" | "entity" => {
+                let kind: InputKind = command.parse()?;
+                let input: Value = serde_json::from_str(argument)?;
+
+                self.print(&self.translator.translate_kind(kind, &input)?)?;
+            }
+            _ if entry.starts_with(['{', '[']) => {
+                let input: Value = serde_json::from_str(entry)?;
+                let translated = self.translator.translate_value(&input)?;
+
+                println!("({})", translated.kind());
+                self.print(&translated)?;
+            }
+            _ => bail!("unknown command: {command}, type help for the commands"),
+        }
+
+        Ok(())
+    }
+
+    fn print<T: std::fmt::Debug + Serialize>(&self, result: &T) -> Result<()> {
+        match self.format {
+            OutputFormat::Debug => println!("{result:#?}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether an entry is a pasted payload, response or entity rather than a command.
+fn is_document(entry: &str) -> bool {
+    entry.starts_with(['{', '['])
+        || matches!(
+            entry.split_whitespace().next(),
+            Some("payload" | "response" | "entity")
+        )
+}
+
+/// Nesting of json brackets at the end of `text`, strings skipped.
+fn json_depth(text: &str) -> i32 {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' if !in_string => depth += 1,
+            '}' | ']' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth
+}
+
+/// Next entry, json pasted over several lines is read until it is closed.
+/// `None` at the end of the input.
+fn read_entry(
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+    interactive: bool,
+) -> Result<Option<String>> {
+    let mut entry = String::new();
+
+    loop {
+        if interactive {
+            print!("{}", if entry.is_empty() { "> " } else { ". " });
+            io::stdout().flush()?;
+        }
+
+        let Some(line) = lines.next().transpose()? else {
+            return Ok((!entry.is_empty()).then_some(entry));
+        };
+
+        if !entry.is_empty() {
+            entry.push('\n');
+        }
+        entry.push_str(&line);
+
+        if json_depth(&entry) <= 0 {
+            return Ok(Some(entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn with_history(history: &[&str]) -> Repl {
+        Repl {
+            translator: Translator::new(HashMap::new()),
+            format: OutputFormat::Json,
+            history: history.iter().map(|entry| entry.to_string()).collect(),
+            history_path: None,
+        }
+    }
+
+    fn lines(text: &str) -> impl Iterator<Item = io::Result<String>> {
+        text.lines()
+            .map(|line| Ok(line.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn expands_history_entries() {
+        let repl = with_history(&["lookup Type", "schema"]);
+
+        assert_eq!(repl.expand("lookup Status").unwrap(), "lookup Status");
+        assert_eq!(repl.expand("!!").unwrap(), "schema");
+        assert_eq!(repl.expand("!1").unwrap(), "lookup Type");
+        assert!(repl.expand("!0").is_err());
+        assert!(repl.expand("!3").is_err());
+        assert!(repl.expand("!x").is_err());
+        assert!(with_history(&[]).expand("!!").is_err());
+    }
+
+    #[test]
+    fn remembers_commands_only() {
+        let mut repl = with_history(&[]);
+
+        repl.remember("lookup Type");
+        repl.remember("lookup Type");
+        repl.remember("{\n  \"oa_1\": 1\n}");
+        repl.remember("payload {\"search_query\": null}");
+        repl.remember("entity {}");
+        repl.remember("query (AND\n  (is_present Type))");
+
+        assert_eq!(
+            repl.history,
+            ["lookup Type", "query (AND (is_present Type))"]
+        );
+    }
+
+    #[test]
+    fn reads_json_over_several_lines() {
+        let mut input = lines("lookup Type\n{\n  \"a\": [1,\n 2]\n}\n{\"b\": \"}\"}\n[");
+
+        assert_eq!(
+            read_entry(&mut input, false).unwrap().as_deref(),
+            Some("lookup Type")
+        );
+        assert_eq!(
+            read_entry(&mut input, false).unwrap().as_deref(),
+            Some("{\n  \"a\": [1,\n 2]\n}")
+        );
+        assert_eq!(
+            read_entry(&mut input, false).unwrap().as_deref(),
+            Some("{\"b\": \"}\"}")
+        );
+        // Unclosed json at the end of the input is given as it is.
+        assert_eq!(read_entry(&mut input, false).unwrap().as_deref(), Some("["));
+        assert_eq!(read_entry(&mut input, false).unwrap(), None);
+    }
+
+    #[test]
+    fn measures_json_depth() {
+        assert_eq!(json_depth("lookup Type"), 0);
+        assert_eq!(json_depth("{\"a\": ["), 2);
+        assert_eq!(json_depth("{\"a\": [1]}"), 0);
+        assert_eq!(json_depth("{\"a\": \"{[\""), 1);
+        assert_eq!(json_depth("{\"a\": \"\\\"{\""), 1);
+        assert_eq!(json_depth("]"), -1);
+    }
+}
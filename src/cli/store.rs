@@ -0,0 +1,111 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use super::output;
+
+const EXTENSION: &str = "snapshot";
+
+/// References imported under a name, kept as snapshots in the user data dir.
+pub struct Store {
+    root: PathBuf,
+}
+
+/// Directory of the data kept between runs, e.g. stored references.
+pub fn data_dir() -> Result<PathBuf> {
+    Ok(env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .context("neither XDG_DATA_HOME nor HOME is set")?
+        .join("json-replacer"))
+}
+
+impl Store {
+    pub fn open() -> Result<Store> {
+        Ok(Store {
+            root: data_dir()?.join("references"),
+        })
+    }
+
+    /// Path of the snapshot stored as `name`, e.g. `tenant-a/orders`.
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name.split('/').all(|part| {
+                !part.is_empty()
+                    && part != "."
+                    && part != ".."
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+
+        if !valid {
+            bail!("invalid reference name: {name}, use letters, digits, -, _ and . separated by /");
+        }
+
+        Ok(self.root.join(format!("{name}.{EXTENSION}")))
+    }
+
+    /// Store a snapshot as `name`, a stored reference is only replaced with `force`.
+    pub fn add(&self, name: &str, snapshot: &str, force: bool) -> Result<PathBuf> {
+        let path = self.path(name)?;
+
+        output::write(&path, snapshot, force)?;
+
+        Ok(path)
+    }
+
+    pub fn get(&self, name: &str) -> Result<String> {
+        let path = self.path(name)?;
+
+        fs::read_to_string(&path).with_context(|| format!("no stored reference named {name}"))
+    }
+
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.path(name)?;
+
+        fs::remove_file(&path).with_context(|| format!("no stored reference named {name}"))
+    }
+
+    /// Names of all stored references, sorted.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+
+        if self.root.exists() {
+            collect_names(&self.root, &self.root, &mut names)?;
+        }
+
+        names.sort_unstable();
+
+        Ok(names)
+    }
+}
+
+fn collect_names(root: &Path, dir: &Path, names: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_names(root, &path, names)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == EXTENSION)
+        {
+            let name = path.strip_prefix(root)?.with_extension("");
+            let parts: Vec<String> = name
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect();
+
+            names.push(parts.join("/"));
+        }
+    }
+
+    Ok(())
+}
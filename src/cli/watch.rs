@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Time given to editors to finish writing a file before it is read.
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// Files polled for changes of their modification time.
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Watcher {
+            files: paths
+                .into_iter()
+                .map(|path| {
+                    let modified = modified(&path);
+                    (path, modified)
+                })
+                .collect(),
+        }
+    }
+
+    /// Block until files change, returns the changed files. Files being
+    /// replaced, so missing for a moment, count as changed once they are back.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let changed = self.changed();
+
+            if !changed.is_empty() {
+                thread::sleep(SETTLE_DELAY);
+                self.changed();
+
+                return changed;
+            }
+        }
+    }
+
+    fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+
+        for (path, last_modified) in &mut self.files {
+            let modified = modified(path);
+
+            if modified.is_some() && modified != *last_modified {
+                changed.push(path.clone());
+            }
+
+            *last_modified = modified.or(*last_modified);
+        }
+
+        changed
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs::File, process};
+
+    use super::*;
+
+    /// Set the modification time of `path`, seconds after the epoch.
+    fn touch(path: &Path, seconds: u64) {
+        fs::write(path, "{}").unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn reports_changes_once() {
+        let dir = env::temp_dir().join(format!("json-replacer-watch-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("payload.json");
+        touch(&path, 1_000);
+
+        let mut watcher = Watcher::new([path.clone()]);
+
+        assert!(watcher.changed().is_empty());
+
+        touch(&path, 2_000);
+
+        assert_eq!(watcher.changed(), vec![path.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        // Replaced by an editor: missing for a moment, then back.
+        fs::remove_file(&path).unwrap();
+
+        assert!(watcher.changed().is_empty());
+
+        touch(&path, 3_000);
+
+        assert_eq!(watcher.changed(), vec![path.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+pub mod json_schema;
+pub mod rust;
+pub mod typescript;
+
+use std::collections::HashSet;
+
+/// Words of a name or label, split on anything not alphanumeric and on case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            words.push(std::mem::take(&mut word));
+            previous_lowercase = false;
+            continue;
+        }
+
+        if c.is_uppercase() && previous_lowercase {
+            words.push(std::mem::take(&mut word));
+        }
+
+        previous_lowercase = c.is_lowercase() || c.is_numeric();
+        word.push(c);
+    }
+
+    words.push(word);
+    words.retain(|word| !word.is_empty());
+
+    words
+}
+
+/// `PascalCase` identifier of a name, e.g. `Type_Name` becomes `TypeName`.
+pub(crate) fn pascal_case(name: &str) -> String {
+    let identifier: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        })
+        .collect();
+
+    valid_identifier(identifier)
+}
+
+/// `snake_case` identifier of a name, e.g. `Type Name` becomes `type_name`.
+pub(crate) fn snake_case(name: &str) -> String {
+    valid_identifier(
+        words(name)
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_"),
+    )
+}
+
+/// Identifiers can't be empty or start with a digit.
+fn valid_identifier(identifier: String) -> String {
+    match identifier.chars().next() {
+        None => "_".to_string(),
+        Some(c) if c.is_numeric() => format!("_{identifier}"),
+        Some(_) => identifier,
+    }
+}
+
+/// Hands out identifiers, suffixing a number when one is already taken.
+#[derive(Default)]
+pub(crate) struct Identifiers(HashSet<String>);
+
+impl Identifiers {
+    pub(crate) fn unique(&mut self, identifier: String) -> String {
+        let mut candidate = identifier.clone();
+        let mut suffix = 2;
+
+        while !self.0.insert(candidate.clone()) {
+            candidate = format!("{identifier}{suffix}");
+            suffix += 1;
+        }
+
+        candidate
+    }
+}
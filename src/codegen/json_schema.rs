@@ -0,0 +1,146 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::{Error, bail};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    KeyStyle, ObjectAttribute,
+    parse::{ObjectAttributeDataType, nested_children, oa_key, top_level_attributes},
+};
+
+/// Document the generated schema validates.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum JsonSchemaTarget {
+    /// Map of `oa_*` keys to values, e.g. `data[].attributes` of a response.
+    #[default]
+    Entity,
+    /// Payload with `object_entity_attribute_values`.
+    Payload,
+}
+
+impl FromStr for JsonSchemaTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entity" => Ok(JsonSchemaTarget::Entity),
+            "payload" => Ok(JsonSchemaTarget::Payload),
+            _ => bail!("unknown json schema target: {s}, expected one of entity, payload"),
+        }
+    }
+}
+
+impl Display for JsonSchemaTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonSchemaTarget::Entity => write!(f, "entity"),
+            JsonSchemaTarget::Payload => write!(f, "payload"),
+        }
+    }
+}
+
+/// Nullable scalar schema, with an optional string format.
+fn scalar(json_type: &str, format: Option<&str>) -> Value {
+    let mut schema = json!({ "type": [json_type, "null"] });
+
+    if let Some(format) = format {
+        schema["format"] = format.into();
+    }
+
+    schema
+}
+
+/// Schema of an attribute, nested forms in `ancestors` aren't expanded again
+/// below themselves and allow any entry.
+fn attribute_schema<'a>(
+    oa: &'a ObjectAttribute,
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    ancestors: &mut Vec<&'a str>,
+) -> Value {
+    let mut schema = match &oa.data_type {
+        ObjectAttributeDataType::Boolean => scalar("boolean", None),
+        ObjectAttributeDataType::Integer => scalar("integer", None),
+        ObjectAttributeDataType::Currency
+        | ObjectAttributeDataType::Float
+        | ObjectAttributeDataType::Number
+        | ObjectAttributeDataType::Percent => scalar("number", None),
+        ObjectAttributeDataType::Date => scalar("string", Some("date")),
+        ObjectAttributeDataType::Datetime => scalar("string", Some("date-time")),
+        ObjectAttributeDataType::Email => scalar("string", Some("email")),
+        ObjectAttributeDataType::EncryptedString
+        | ObjectAttributeDataType::Id
+        | ObjectAttributeDataType::Phone
+        | ObjectAttributeDataType::Reference
+        | ObjectAttributeDataType::String
+        | ObjectAttributeDataType::Text
+        | ObjectAttributeDataType::Textarea
+        | ObjectAttributeDataType::Richtextarea => scalar("string", None),
+        ObjectAttributeDataType::Address => scalar("object", None),
+        ObjectAttributeDataType::Picklist => {
+            let mut options: Vec<Value> = oa
+                .picklist_options
+                .iter()
+                .map(|option| json!({ "const": option.id, "description": option.display(KeyStyle::Label) }))
+                .collect();
+            options.push(json!({ "type": "null" }));
+
+            json!({ "oneOf": options })
+        }
+        ObjectAttributeDataType::NestedForm => {
+            ancestors.push(&oa.id);
+            let children = nested_children(oa, hashmap, ancestors);
+            let entity = entity_schema(&children, hashmap, ancestors);
+            ancestors.pop();
+
+            json!({
+                "type": ["object", "null"],
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": { "object_entity_attribute_values": entity }
+                }
+            })
+        }
+        ObjectAttributeDataType::File | ObjectAttributeDataType::Unknown(_) => json!({}),
+    };
+
+    schema["title"] = oa.name.clone().into();
+    schema["description"] = format!("{} ({})", oa.label(), oa.data_type).into();
+
+    schema
+}
+
+fn entity_schema<'a>(
+    attributes: &[&'a ObjectAttribute],
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    ancestors: &mut Vec<&'a str>,
+) -> Value {
+    let properties: Map<String, Value> = attributes
+        .iter()
+        .map(|oa| (oa_key(&oa.id), attribute_schema(oa, hashmap, ancestors)))
+        .collect();
+
+    json!({ "type": "object", "properties": properties })
+}
+
+pub fn generate(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    target: JsonSchemaTarget,
+) -> Value {
+    let entity = entity_schema(
+        &top_level_attributes(oa_id_hashmap),
+        oa_id_hashmap,
+        &mut Vec::new(),
+    );
+
+    let mut schema = match target {
+        JsonSchemaTarget::Entity => entity,
+        JsonSchemaTarget::Payload => json!({
+            "type": "object",
+            "properties": { "object_entity_attribute_values": entity }
+        }),
+    };
+
+    schema["$schema"] = "https://json-schema.org/draft/2020-12/schema".into();
+
+    schema
+}
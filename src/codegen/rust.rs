@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use crate::{
+    KeyStyle, ObjectAttribute,
+    codegen::{Identifiers, pascal_case, snake_case},
+    parse::{ObjectAttributeDataType, nested_children, oa_key, object_classes},
+};
+
+/// Keywords that need a raw identifier as field name.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Field name of an attribute name, `field_<n>` when it has no letters or digits.
+fn field_name(name: &str, position: usize) -> String {
+    let identifier = snake_case(name);
+
+    match identifier.as_str() {
+        "_" => format!("field_{position}"),
+        // Can't be raw identifiers.
+        "crate" | "self" | "super" => format!("{identifier}_"),
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{identifier}"),
+        _ => identifier,
+    }
+}
+
+/// Type or variant name of a name or label, `fallback` when it has no letters
+/// or digits or is `Self`, which can't be a raw identifier.
+fn type_name(name: &str, fallback: impl FnOnce() -> String) -> String {
+    match pascal_case(name) {
+        identifier if identifier == "_" || identifier == "Self" => fallback(),
+        identifier => identifier,
+    }
+}
+
+/// String literal, json strings are valid Rust strings.
+fn literal(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// Doc comment of an attribute, e.g. `/// Type_Name: Type Name (picklist)`.
+fn doc(oa: &ObjectAttribute, indent: &str) -> String {
+    let text = format!("{}: {} ({})", oa.name, oa.label(), oa.data_type).replace('\n', " ");
+
+    format!("{indent}/// {text}")
+}
+
+struct Generator<'a> {
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    identifiers: Identifiers,
+    constants: Identifiers,
+    /// Attribute id constants.
+    ids: Vec<String>,
+    /// Picklist enums and nested form structs, in order of use.
+    declarations: Vec<String>,
+    nested_forms: bool,
+    /// Nested forms being generated, not expanded again below themselves.
+    ancestors: Vec<&'a str>,
+}
+
+impl<'a> Generator<'a> {
+    fn constant(&mut self, oa: &ObjectAttribute, position: usize) {
+        let name = match snake_case(&oa.name).to_uppercase() {
+            name if name == "_" => format!("FIELD_{position}"),
+            name => name,
+        };
+        let name = self.constants.unique(name);
+
+        self.ids.push(format!(
+            "{}\npub const {name}: &str = {};",
+            doc(oa, ""),
+            literal(&oa.id)
+        ));
+    }
+
+    /// Enum of the options of a picklist, serialized as option ids.
+    fn picklist(&mut self, oa: &ObjectAttribute) -> String {
+        let name = self
+            .identifiers
+            .unique(format!("{}Option", pascal_case(&oa.name)));
+
+        let mut variants = Identifiers::default();
+        let variants: String = oa
+            .picklist_options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| {
+                let label = option.display(KeyStyle::Label);
+                let variant = variants.unique(type_name(label, || format!("Variant{}", index + 1)));
+
+                format!(
+                    "    /// {}\n    #[serde(rename = {})]\n    {variant},\n",
+                    label.replace('\n', " "),
+                    literal(&option.id)
+                )
+            })
+            .collect();
+
+        self.declarations.push(format!(
+            "{}\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]\npub enum {name} {{\n{variants}}}",
+            doc(oa, "")
+        ));
+
+        name
+    }
+
+    /// Struct of a nested form entry, the form maps entry ids to entries.
+    fn nested_form(&mut self, oa: &'a ObjectAttribute) -> String {
+        let name = self
+            .identifiers
+            .unique(format!("{}Entry", pascal_case(&oa.name)));
+
+        self.ancestors.push(&oa.id);
+        let children = nested_children(oa, self.hashmap, &self.ancestors);
+        let entry = self.entity(&name, &children);
+        self.ancestors.pop();
+        self.declarations.push(format!("{}\n{entry}", doc(oa, "")));
+        self.nested_forms = true;
+
+        format!("HashMap<String, NestedFormEntry<{name}>>")
+    }
+
+    fn field_type(&mut self, oa: &'a ObjectAttribute) -> String {
+        match &oa.data_type {
+            ObjectAttributeDataType::Boolean => "bool".to_string(),
+            ObjectAttributeDataType::Integer => "i64".to_string(),
+            ObjectAttributeDataType::Currency
+            | ObjectAttributeDataType::Float
+            | ObjectAttributeDataType::Number
+            | ObjectAttributeDataType::Percent => "f64".to_string(),
+            ObjectAttributeDataType::Date
+            | ObjectAttributeDataType::Datetime
+            | ObjectAttributeDataType::Email
+            | ObjectAttributeDataType::EncryptedString
+            | ObjectAttributeDataType::Id
+            | ObjectAttributeDataType::Phone
+            | ObjectAttributeDataType::Reference
+            | ObjectAttributeDataType::String
+            | ObjectAttributeDataType::Text
+            | ObjectAttributeDataType::Textarea
+            | ObjectAttributeDataType::Richtextarea => "String".to_string(),
+            ObjectAttributeDataType::Picklist => self.picklist(oa),
+            ObjectAttributeDataType::NestedForm => self.nested_form(oa),
+            ObjectAttributeDataType::Address
+            | ObjectAttributeDataType::File
+            | ObjectAttributeDataType::Unknown(_) => "serde_json::Value".to_string(),
+        }
+    }
+
+    /// Struct with an optional field per attribute, keyed by `oa_*` id in json.
+    fn entity(&mut self, name: &str, attributes: &[&'a ObjectAttribute]) -> String {
+        let mut fields = Identifiers::default();
+        let fields: String = attributes
+            .iter()
+            .enumerate()
+            .map(|(index, oa)| {
+                self.constant(oa, index + 1);
+
+                format!(
+                    "{}\n    #[serde(rename = {}, default, skip_serializing_if = \"Option::is_none\")]\n    pub {}: Option<{}>,\n",
+                    doc(oa, "    "),
+                    literal(&oa_key(&oa.id)),
+                    fields.unique(field_name(&oa.name, index + 1)),
+                    self.field_type(oa)
+                )
+            })
+            .collect();
+
+        format!(
+            "#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]\npub struct {name} {{\n{fields}}}"
+        )
+    }
+}
+
+pub fn generate(oa_id_hashmap: &HashMap<String, ObjectAttribute>, name: &str) -> String {
+    let name = type_name(name, || "ObjectEntity".to_string());
+    let classes = object_classes(oa_id_hashmap);
+
+    let mut generator = Generator {
+        hashmap: oa_id_hashmap,
+        identifiers: Identifiers::default(),
+        constants: Identifiers::default(),
+        ids: Vec::new(),
+        declarations: Vec::new(),
+        nested_forms: false,
+        ancestors: Vec::new(),
+    };
+    generator.identifiers.unique("NestedFormEntry".to_string());
+
+    // Structs are named after the object class id when a reference has several classes.
+    let structs: Vec<String> = classes
+        .iter()
+        .map(|(object_class_id, attributes)| {
+            let struct_name = match object_class_id {
+                Some(id) if classes.len() > 1 => {
+                    let prefix = id.split('-').next().unwrap_or(id);
+                    format!("{name}{}", pascal_case(prefix).trim_start_matches('_'))
+                }
+                _ => name.clone(),
+            };
+            let struct_name = generator.identifiers.unique(struct_name);
+
+            let doc = match object_class_id {
+                Some(id) => format!("/// Entity of object class `{id}`.\n"),
+                None => String::new(),
+            };
+
+            format!("{doc}{}", generator.entity(&struct_name, attributes))
+        })
+        .collect();
+
+    let mut blocks =
+        vec!["// Generated by json-replacer from the reference, do not edit.".to_string()];
+
+    if generator.nested_forms {
+        blocks.push(
+            "use std::collections::HashMap;\n\nuse serde::{Deserialize, Serialize};".to_string(),
+        );
+        blocks.push(
+            "/// Entry of a nested form.\n#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]\npub struct NestedFormEntry<T> {\n    pub object_entity_attribute_values: T,\n}"
+                .to_string(),
+        );
+    } else {
+        blocks.push("use serde::{Deserialize, Serialize};".to_string());
+    }
+
+    blocks.append(&mut generator.ids);
+    blocks.append(&mut generator.declarations);
+    blocks.extend(structs);
+
+    blocks.join("\n\n") + "\n"
+}
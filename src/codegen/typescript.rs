@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use crate::{
+    KeyStyle, ObjectAttribute,
+    codegen::{Identifiers, pascal_case},
+    parse::{ObjectAttributeDataType, nested_children, oa_key, top_level_attributes},
+};
+
+/// String literal, json strings are valid TypeScript strings.
+fn literal(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// JSDoc comment of an attribute, e.g. `/** Type_Name: Type Name (picklist) */`.
+fn doc(oa: &ObjectAttribute, indent: &str) -> String {
+    let text = format!("{}: {} ({})", oa.name, oa.label(), oa.data_type).replace("*/", "*\\/");
+
+    format!("{indent}/** {text} */")
+}
+
+struct Generator<'a> {
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    identifiers: Identifiers,
+    /// Option unions, their constants and nested form interfaces, in order of use.
+    declarations: Vec<String>,
+    /// Nested forms being generated, not expanded again below themselves.
+    ancestors: Vec<&'a str>,
+}
+
+impl<'a> Generator<'a> {
+    /// Option id union and a constant of option ids named by label.
+    fn picklist(&mut self, oa: &ObjectAttribute) -> String {
+        let name = self
+            .identifiers
+            .unique(format!("{}Option", pascal_case(&oa.name)));
+
+        let union = if oa.picklist_options.is_empty() {
+            " never".to_string()
+        } else {
+            oa.picklist_options
+                .iter()
+                .map(|option| format!("\n  | {}", literal(&option.id)))
+                .collect()
+        };
+
+        let mut keys = Identifiers::default();
+        let constants: String = oa
+            .picklist_options
+            .iter()
+            .map(|option| {
+                let key = keys.unique(pascal_case(option.display(KeyStyle::Label)));
+                format!("  {key}: {},\n", literal(&option.id))
+            })
+            .collect();
+
+        self.declarations.push(format!(
+            "{}\nexport type {name} ={union};\n\nexport const {name} = {{\n{constants}}} as const;",
+            doc(oa, "")
+        ));
+
+        name
+    }
+
+    /// Interface of a nested form entry, the form maps entry ids to entries.
+    fn nested_form(&mut self, oa: &'a ObjectAttribute) -> String {
+        let name = self
+            .identifiers
+            .unique(format!("{}Entry", pascal_case(&oa.name)));
+
+        self.ancestors.push(&oa.id);
+        let children = nested_children(oa, self.hashmap, &self.ancestors);
+        let interface = self.interface(&name, &children);
+        self.ancestors.pop();
+        self.declarations
+            .push(format!("{}\n{interface}", doc(oa, "")));
+
+        format!("Record<string, {{ object_entity_attribute_values: {name} }}>")
+    }
+
+    fn property_type(&mut self, oa: &'a ObjectAttribute) -> String {
+        match &oa.data_type {
+            ObjectAttributeDataType::Boolean => "boolean".to_string(),
+            ObjectAttributeDataType::Currency
+            | ObjectAttributeDataType::Float
+            | ObjectAttributeDataType::Integer
+            | ObjectAttributeDataType::Number
+            | ObjectAttributeDataType::Percent => "number".to_string(),
+            ObjectAttributeDataType::Date
+            | ObjectAttributeDataType::Datetime
+            | ObjectAttributeDataType::Email
+            | ObjectAttributeDataType::EncryptedString
+            | ObjectAttributeDataType::Id
+            | ObjectAttributeDataType::Phone
+            | ObjectAttributeDataType::Reference
+            | ObjectAttributeDataType::String
+            | ObjectAttributeDataType::Text
+            | ObjectAttributeDataType::Textarea
+            | ObjectAttributeDataType::Richtextarea => "string".to_string(),
+            ObjectAttributeDataType::Address => "Record<string, unknown>".to_string(),
+            ObjectAttributeDataType::Picklist => self.picklist(oa),
+            ObjectAttributeDataType::NestedForm => self.nested_form(oa),
+            ObjectAttributeDataType::File | ObjectAttributeDataType::Unknown(_) => {
+                "unknown".to_string()
+            }
+        }
+    }
+
+    fn interface(&mut self, name: &str, attributes: &[&'a ObjectAttribute]) -> String {
+        let properties: String = attributes
+            .iter()
+            .map(|oa| {
+                format!(
+                    "{}\n  {}?: {} | null;\n",
+                    doc(oa, "  "),
+                    oa_key(&oa.id),
+                    self.property_type(oa)
+                )
+            })
+            .collect();
+
+        format!("export interface {name} {{\n{properties}}}")
+    }
+}
+
+pub fn generate(oa_id_hashmap: &HashMap<String, ObjectAttribute>, name: &str) -> String {
+    let name = pascal_case(name);
+    let names_name = format!("{name}Names");
+    let by_name_name = format!("{name}ByName");
+
+    let mut generator = Generator {
+        hashmap: oa_id_hashmap,
+        identifiers: Identifiers::default(),
+        declarations: Vec::new(),
+        ancestors: Vec::new(),
+    };
+
+    for reserved in [&name, &names_name, &by_name_name] {
+        generator.identifiers.unique(reserved.clone());
+    }
+
+    let attributes = top_level_attributes(oa_id_hashmap);
+    let interface = generator.interface(&name, &attributes);
+
+    let names: String = attributes
+        .iter()
+        .map(|oa| format!("  {}: {};\n", oa_key(&oa.id), literal(&oa.name)))
+        .collect();
+
+    let mut blocks =
+        vec!["// Generated by json-replacer from the reference, do not edit.".to_string()];
+    blocks.append(&mut generator.declarations);
+    blocks.push(interface);
+    blocks.push(format!(
+        "/** Attribute names by `oa_*` key. */\nexport interface {names_name} {{\n{names}}}"
+    ));
+    blocks.push(format!(
+        "/** `{name}` keyed by attribute names. */\nexport type {by_name_name} = {{\n  [K in keyof {name} as K extends keyof {names_name} ? {names_name}[K] : never]: {name}[K];\n}};"
+    ));
+
+    blocks.join("\n\n") + "\n"
+}
@@ -0,0 +1,266 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::{Error, bail};
+
+use crate::{
+    ObjectAttribute,
+    parse::{PicklistOption, nested_children, object_classes},
+    schema::markdown_cell,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum DictionaryFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl FromStr for DictionaryFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(DictionaryFormat::Markdown),
+            "html" => Ok(DictionaryFormat::Html),
+            _ => bail!("unknown dictionary format: {s}, expected one of markdown, html"),
+        }
+    }
+}
+
+impl Display for DictionaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DictionaryFormat::Markdown => write!(f, "markdown"),
+            DictionaryFormat::Html => write!(f, "html"),
+        }
+    }
+}
+
+/// Object class with its attributes, nested form children following their form.
+struct Section<'a> {
+    anchor: String,
+    title: String,
+    rows: Vec<Row<'a>>,
+}
+
+struct Row<'a> {
+    anchor: String,
+    /// Name, prefixed by the names of enclosing nested forms.
+    name: String,
+    oa: &'a ObjectAttribute,
+}
+
+/// Rows of an attribute and its nested form children, forms in `ancestors`
+/// aren't listed again below themselves.
+fn push_rows<'a>(
+    oa: &'a ObjectAttribute,
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    parent: Option<&str>,
+    ancestors: &mut Vec<&'a str>,
+    rows: &mut Vec<Row<'a>>,
+) {
+    let name = match parent {
+        Some(parent) => format!("{parent} / {}", oa.name),
+        None => oa.name.clone(),
+    };
+
+    rows.push(Row {
+        anchor: format!("oa-{}", oa.id),
+        name: name.clone(),
+        oa,
+    });
+
+    ancestors.push(&oa.id);
+
+    for child in nested_children(oa, hashmap, ancestors) {
+        push_rows(child, hashmap, Some(&name), ancestors, rows);
+    }
+
+    ancestors.pop();
+}
+
+fn sections(hashmap: &HashMap<String, ObjectAttribute>) -> Vec<Section<'_>> {
+    object_classes(hashmap)
+        .into_iter()
+        .map(|(object_class_id, attributes)| {
+            let mut rows = Vec::new();
+
+            for oa in attributes {
+                push_rows(oa, hashmap, None, &mut Vec::new(), &mut rows);
+            }
+
+            let (anchor, title) = match object_class_id {
+                Some(id) => (format!("class-{id}"), format!("Object class {id}")),
+                None => ("attributes".to_string(), "Attributes".to_string()),
+            };
+
+            Section {
+                anchor,
+                title,
+                rows,
+            }
+        })
+        .collect()
+}
+
+fn option_text(option: &PicklistOption) -> String {
+    let label = if option.label.is_empty() {
+        &option.name
+    } else {
+        &option.label
+    };
+
+    if option.disabled {
+        format!("{label} [disabled]")
+    } else {
+        label.to_string()
+    }
+}
+
+/// Markdown may contain html, so text is escaped like in the html dictionary.
+fn render_markdown(title: &str, sections: &[Section]) -> String {
+    let mut lines = vec![format!("# {}", escape_html(title)), String::new()];
+
+    for section in sections {
+        lines.push(format!(
+            "- [{}](#{})",
+            escape_html(&section.title),
+            section.anchor
+        ));
+    }
+
+    for section in sections {
+        lines.push(String::new());
+        lines.push(format!("<a id=\"{}\"></a>", escape_html(&section.anchor)));
+        lines.push(String::new());
+        lines.push(format!("## {}", escape_html(&section.title)));
+        lines.push(String::new());
+        lines.push("| Label | Name | ID | Data type | Picklist options |".to_string());
+        lines.push("| --- | --- | --- | --- | --- |".to_string());
+
+        for row in &section.rows {
+            let options = row
+                .oa
+                .picklist_options
+                .iter()
+                .map(|option| format!("{} (`{}`)", escape_html(&option_text(option)), option.id))
+                .collect::<Vec<String>>()
+                .join("<br>");
+
+            lines.push(format!(
+                "| <a id=\"{}\"></a>{} | {} | `{}` | {} | {} |",
+                escape_html(&row.anchor),
+                markdown_cell(&escape_html(row.oa.label())),
+                markdown_cell(&escape_html(&row.name)),
+                row.oa.id,
+                row.oa.data_type,
+                markdown_cell(&options)
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ccc; padding: 0.4rem; text-align: left; vertical-align: top; }
+th { background: #f4f4f4; }
+td ul { margin: 0; padding-left: 1.2rem; }
+code { font-size: 0.9em; }";
+
+fn render_html(title: &str, sections: &[Section]) -> String {
+    let title = escape_html(title);
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html>".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        format!("<title>{title}</title>"),
+        format!("<style>\n{STYLE}\n</style>"),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        format!("<h1>{title}</h1>"),
+        "<nav>".to_string(),
+        "<ul>".to_string(),
+    ];
+
+    for section in sections {
+        lines.push(format!(
+            "<li><a href=\"#{}\">{}</a></li>",
+            escape_html(&section.anchor),
+            escape_html(&section.title)
+        ));
+    }
+
+    lines.push("</ul>".to_string());
+    lines.push("</nav>".to_string());
+
+    for section in sections {
+        lines.push(format!("<section id=\"{}\">", escape_html(&section.anchor)));
+        lines.push(format!("<h2>{}</h2>", escape_html(&section.title)));
+        lines.push("<table>".to_string());
+        lines.push(
+            "<tr><th>Label</th><th>Name</th><th>ID</th><th>Data type</th><th>Picklist options</th></tr>"
+                .to_string(),
+        );
+
+        for row in &section.rows {
+            let options = if row.oa.picklist_options.is_empty() {
+                String::new()
+            } else {
+                let items: String = row
+                    .oa
+                    .picklist_options
+                    .iter()
+                    .map(|option| {
+                        format!(
+                            "<li>{} <code>{}</code></li>",
+                            escape_html(&option_text(option)),
+                            escape_html(&option.id)
+                        )
+                    })
+                    .collect();
+
+                format!("<ul>{items}</ul>")
+            };
+
+            lines.push(format!(
+                "<tr id=\"{}\"><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td><td>{options}</td></tr>",
+                escape_html(&row.anchor),
+                escape_html(row.oa.label()),
+                escape_html(&row.name),
+                escape_html(&row.oa.id),
+                row.oa.data_type
+            ));
+        }
+
+        lines.push("</table>".to_string());
+        lines.push("</section>".to_string());
+    }
+
+    lines.push("</body>".to_string());
+    lines.push("</html>".to_string());
+
+    lines.join("\n") + "\n"
+}
+
+pub fn render(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    title: &str,
+    format: DictionaryFormat,
+) -> String {
+    let sections = sections(oa_id_hashmap);
+
+    match format {
+        DictionaryFormat::Markdown => render_markdown(title, &sections),
+        DictionaryFormat::Html => render_html(title, &sections),
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use serde::Serialize;
+
+use crate::{ObjectAttribute, parse::PicklistOption};
+
+/// Differences between two parsed references.
+#[derive(Serialize, Default)]
+pub struct ReferenceDiff {
+    pub added_attributes: Vec<AttributeChange>,
+    pub removed_attributes: Vec<AttributeChange>,
+    /// Attributes with the same id and a new name.
+    pub renamed_attributes: Vec<Rename>,
+    pub changed_data_types: Vec<DataTypeChange>,
+    pub added_options: Vec<OptionChange>,
+    pub removed_options: Vec<OptionChange>,
+    /// Options with the same id and a new name.
+    pub renamed_options: Vec<OptionRename>,
+}
+
+#[derive(Serialize)]
+pub struct AttributeChange {
+    pub id: String,
+    pub name: String,
+    pub data_type: String,
+}
+
+#[derive(Serialize)]
+pub struct Rename {
+    pub id: String,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Serialize)]
+pub struct DataTypeChange {
+    pub id: String,
+    pub name: String,
+    pub old_data_type: String,
+    pub new_data_type: String,
+}
+
+#[derive(Serialize)]
+pub struct OptionChange {
+    pub object_attribute_id: String,
+    pub object_attribute_name: String,
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct OptionRename {
+    pub object_attribute_id: String,
+    pub object_attribute_name: String,
+    pub id: String,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+impl ReferenceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_attributes.is_empty()
+            && self.removed_attributes.is_empty()
+            && self.renamed_attributes.is_empty()
+            && self.changed_data_types.is_empty()
+            && self.added_options.is_empty()
+            && self.removed_options.is_empty()
+            && self.renamed_options.is_empty()
+    }
+}
+
+impl Display for ReferenceDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No differences found.");
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+
+        for oa in &self.added_attributes {
+            lines.push(format!("+ {} ({}) {}", oa.name, oa.data_type, oa.id));
+        }
+
+        for oa in &self.removed_attributes {
+            lines.push(format!("- {} ({}) {}", oa.name, oa.data_type, oa.id));
+        }
+
+        for rename in &self.renamed_attributes {
+            lines.push(format!(
+                "~ {} renamed to {} {}",
+                rename.old_name, rename.new_name, rename.id
+            ));
+        }
+
+        for change in &self.changed_data_types {
+            lines.push(format!(
+                "~ {} data type {} changed to {} {}",
+                change.name, change.old_data_type, change.new_data_type, change.id
+            ));
+        }
+
+        for option in &self.added_options {
+            lines.push(format!(
+                "+ {}: option {} {}",
+                option.object_attribute_name, option.name, option.id
+            ));
+        }
+
+        for option in &self.removed_options {
+            lines.push(format!(
+                "- {}: option {} {}",
+                option.object_attribute_name, option.name, option.id
+            ));
+        }
+
+        for rename in &self.renamed_options {
+            lines.push(format!(
+                "~ {}: option {} renamed to {} {}",
+                rename.object_attribute_name, rename.old_name, rename.new_name, rename.id
+            ));
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+fn attribute_change(oa: &ObjectAttribute) -> AttributeChange {
+    AttributeChange {
+        id: oa.id.clone(),
+        name: oa.name.clone(),
+        data_type: oa.data_type.to_string(),
+    }
+}
+
+fn diff_options(old: &ObjectAttribute, new: &ObjectAttribute, diff: &mut ReferenceDiff) {
+    let option_change = |option: &PicklistOption| OptionChange {
+        object_attribute_id: new.id.clone(),
+        object_attribute_name: new.name.clone(),
+        id: option.id.clone(),
+        name: option.name.clone(),
+    };
+
+    for option in &new.picklist_options {
+        match old.option(&option.id) {
+            Some(old_option) if old_option.name != option.name => {
+                diff.renamed_options.push(OptionRename {
+                    object_attribute_id: new.id.clone(),
+                    object_attribute_name: new.name.clone(),
+                    id: option.id.clone(),
+                    old_name: old_option.name.clone(),
+                    new_name: option.name.clone(),
+                });
+            }
+            Some(_) => {}
+            None => diff.added_options.push(option_change(option)),
+        }
+    }
+
+    for option in &old.picklist_options {
+        if new.option(&option.id).is_none() {
+            diff.removed_options.push(option_change(option));
+        }
+    }
+}
+
+pub fn diff(
+    old: &HashMap<String, ObjectAttribute>,
+    new: &HashMap<String, ObjectAttribute>,
+) -> ReferenceDiff {
+    let mut diff = ReferenceDiff::default();
+    let ids: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    for id in ids {
+        match (old.get(id), new.get(id)) {
+            (None, Some(new_oa)) => diff.added_attributes.push(attribute_change(new_oa)),
+            (Some(old_oa), None) => diff.removed_attributes.push(attribute_change(old_oa)),
+            (Some(old_oa), Some(new_oa)) => {
+                if old_oa.name != new_oa.name {
+                    diff.renamed_attributes.push(Rename {
+                        id: id.clone(),
+                        old_name: old_oa.name.clone(),
+                        new_name: new_oa.name.clone(),
+                    });
+                }
+
+                if old_oa.data_type != new_oa.data_type {
+                    diff.changed_data_types.push(DataTypeChange {
+                        id: id.clone(),
+                        name: new_oa.name.clone(),
+                        old_data_type: old_oa.data_type.to_string(),
+                        new_data_type: new_oa.data_type.to_string(),
+                    });
+                }
+
+                diff_options(old_oa, new_oa, &mut diff);
+            }
+            (None, None) => {}
+        }
+    }
+
+    diff
+}
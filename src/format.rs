@@ -0,0 +1,220 @@
+use std::{borrow::Cow, sync::Arc};
+
+use jiff::{civil, tz::TimeZone};
+use serde_json::{Map, Value};
+
+use crate::{
+    ObjectAttribute, Options,
+    parse::{ObjectAttributeDataType, parse_oa_uuid},
+    timezone::{parse_timestamp, rfc3339},
+};
+
+/// Custom rendering of attribute values, e.g. decoding internal ids kept in
+/// text attributes. Consulted before the built-in translation of entity
+/// values and search condition values, after redaction.
+///
+/// Closures taking the attribute and the raw value implement it too.
+pub trait ValueFormatter: Send + Sync {
+    /// Rendered value, or `None` to leave it to the built-in translation.
+    fn format(&self, attribute: &ObjectAttribute, value: &Value) -> Option<Value>;
+}
+
+impl<F> ValueFormatter for F
+where
+    F: Fn(&ObjectAttribute, &Value) -> Option<Value> + Send + Sync,
+{
+    fn format(&self, attribute: &ObjectAttribute, value: &Value) -> Option<Value> {
+        self(attribute, value)
+    }
+}
+
+/// Values a formatter is registered for.
+#[derive(Clone, Debug)]
+pub enum FormatterTarget {
+    /// Values of every attribute of this data type.
+    DataType(ObjectAttributeDataType),
+    /// Values of one attribute, by id with or without `oa_` prefix.
+    Attribute(String),
+}
+
+/// Registered value formatters, see [`Options::register_formatter`].
+#[derive(Clone, Default)]
+pub struct Formatters(Vec<(FormatterTarget, Arc<dyn ValueFormatter>)>);
+
+impl Formatters {
+    pub fn register(&mut self, target: FormatterTarget, formatter: impl ValueFormatter + 'static) {
+        let target = match target {
+            FormatterTarget::Attribute(id) => FormatterTarget::Attribute(parse_oa_uuid(&id)),
+            target => target,
+        };
+
+        self.0.push((target, Arc::new(formatter)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Value rendered by the formatters of the attribute, then those of its data
+    /// type, the latest registered first. `None` when none of them renders it.
+    pub(crate) fn format(&self, oa: &ObjectAttribute, value: &Value) -> Option<Value> {
+        let attribute = self.0.iter().rev().filter(|(target, _)| match target {
+            FormatterTarget::Attribute(id) => *id == oa.id,
+            FormatterTarget::DataType(_) => false,
+        });
+        let data_type = self.0.iter().rev().filter(|(target, _)| match target {
+            FormatterTarget::DataType(data_type) => *data_type == oa.data_type,
+            FormatterTarget::Attribute(_) => false,
+        });
+
+        attribute
+            .chain(data_type)
+            .find_map(|(_, formatter)| formatter.format(oa, value))
+    }
+}
+
+/// Address fields in the order they are written on one line.
+const ADDRESS_FIELDS: [&str; 14] = [
+    "line1",
+    "line2",
+    "street",
+    "street_address",
+    "address1",
+    "address2",
+    "city",
+    "state",
+    "region",
+    "province",
+    "postal_code",
+    "zip",
+    "zip_code",
+    "country",
+];
+
+/// Currencies without minor units, most have two.
+const ZERO_DECIMAL_CURRENCIES: [&str; 9] = [
+    "CLP", "ISK", "JPY", "KRW", "PYG", "UGX", "VND", "XAF", "XOF",
+];
+
+/// Currencies with three minor units.
+const THREE_DECIMAL_CURRENCIES: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+fn currency_precision(code: &str) -> usize {
+    if ZERO_DECIMAL_CURRENCIES.contains(&code) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&code) {
+        3
+    } else {
+        2
+    }
+}
+
+/// Number of a json number or numeric string.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn format_date(value: &str) -> Option<String> {
+    let date = value.parse::<civil::Date>().ok().or_else(|| {
+        parse_timestamp(value).map(|timestamp| timestamp.to_zoned(TimeZone::UTC).date())
+    })?;
+
+    Some(date.to_string())
+}
+
+/// Datetime in the configured timezone, human readable in display mode.
+fn format_datetime(value: &str, options: &Options) -> Option<String> {
+    if !options.display && options.timezone.is_none() {
+        return None;
+    }
+
+    let timezone = options.timezone.clone().unwrap_or_default();
+    let zoned = timezone.to_zoned(parse_timestamp(value)?);
+
+    if options.display {
+        Some(zoned.strftime("%Y-%m-%d %H:%M:%S %Z").to_string())
+    } else {
+        Some(rfc3339(&zoned))
+    }
+}
+
+fn format_percent(value: &Value) -> Option<String> {
+    number(value)?;
+
+    match value {
+        Value::String(s) => Some(format!("{}%", s.trim())),
+        _ => Some(format!("{value}%")),
+    }
+}
+
+/// Amount with the precision of the configured currency and its code, rounded
+/// half away from zero. Amounts are plain numbers, as in generated types.
+fn format_currency(value: &Value, options: &Options) -> Option<String> {
+    let amount = number(value)?;
+    let code = options.currency.as_ref().map(|code| code.to_uppercase());
+    let precision = code.as_deref().map_or(2, currency_precision);
+
+    let factor = 10f64.powi(precision as i32);
+    let amount = (amount * factor).round() / factor;
+
+    Some(match code {
+        Some(code) => format!("{amount:.precision$} {code}"),
+        None => format!("{amount:.precision$}"),
+    })
+}
+
+fn format_boolean(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Bool(true) => Some("yes"),
+        Value::Bool(false) => Some("no"),
+        Value::String(s) if s.eq_ignore_ascii_case("true") => Some("yes"),
+        Value::String(s) if s.eq_ignore_ascii_case("false") => Some("no"),
+        _ => None,
+    }
+}
+
+/// Known address fields in order, then any other text fields, joined by commas.
+fn format_address(map: &Map<String, Value>) -> String {
+    let text = |value: &Value| match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    };
+
+    ADDRESS_FIELDS
+        .iter()
+        .filter_map(|field| map.get(*field).and_then(text))
+        .chain(
+            map.iter()
+                .filter(|(key, _)| !ADDRESS_FIELDS.contains(&key.as_str()))
+                .filter_map(|(_, value)| text(value)),
+        )
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Value formatted for display by its data type, values that can't be
+/// formatted are returned unchanged. Datetimes are also moved to the
+/// configured timezone outside of display mode.
+pub(crate) fn format_value(
+    data_type: &ObjectAttributeDataType,
+    value: Cow<Value>,
+    options: &Options,
+) -> Value {
+    let formatted = match (data_type, value.as_ref()) {
+        (ObjectAttributeDataType::Datetime, Value::String(s)) => format_datetime(s, options),
+        _ if !options.display => None,
+        (ObjectAttributeDataType::Date, Value::String(s)) => format_date(s),
+        (ObjectAttributeDataType::Percent, _) => format_percent(&value),
+        (ObjectAttributeDataType::Currency, _) => format_currency(&value, options),
+        (ObjectAttributeDataType::Boolean, _) => format_boolean(&value).map(str::to_string),
+        (ObjectAttributeDataType::Address, Value::Object(map)) => Some(format_address(map)),
+        _ => None,
+    };
+
+    formatted.map_or_else(|| value.into_owned(), Value::String)
+}
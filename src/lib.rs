@@ -1,4 +1,17 @@
+mod codegen;
+mod dictionary;
+mod diff;
+mod format;
+mod lookup;
+mod options;
 mod parse;
+mod query;
+mod schema;
+mod snapshot;
+mod stream;
+mod timezone;
+mod translator;
+mod validate;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm;
@@ -9,13 +22,27 @@ use anyhow::Result;
 use serde_json::Value;
 
 use parse::{
-    object_entity::parse as parse_entity,
-    payload::parse as parse_payload,
-    reference::{ObjectAttributesRaw, parse as parse_reference},
-    response::parse as parse_response,
+    object_entity::parse as parse_entity, payload::parse as parse_payload,
+    reference::parse as parse_reference, response::parse as parse_response,
 };
 
-pub use parse::{ObjectAttribute, payload::Payload, response::Response};
+pub use codegen::json_schema::JsonSchemaTarget;
+pub use dictionary::DictionaryFormat;
+pub use diff::{
+    AttributeChange, DataTypeChange, OptionChange, OptionRename, ReferenceDiff, Rename,
+};
+pub use format::{FormatterTarget, Formatters, ValueFormatter};
+pub use lookup::{LookupKind, LookupMatch};
+pub use options::{AttributeOrder, KeyStyle, Options};
+pub use parse::reference::ObjectAttributesRaw;
+pub use parse::{ObjectAttribute, ObjectAttributeDataType, payload::Payload, response::Response};
+pub use schema::{SchemaFilter, SchemaFormat};
+pub use stream::{EntityWriter, StreamFormat};
+pub use timezone::Timezone;
+pub use translator::{
+    Entity, InputKind, Translated, TranslatedPayload, Translator, TranslatorBuilder,
+};
+pub use validate::{Duplicate, MissingOption, ReferenceReport};
 
 pub fn preprocess_reference(
     reference: ObjectAttributesRaw,
@@ -23,23 +50,146 @@ pub fn preprocess_reference(
     parse_reference(reference)
 }
 
+/// One reference of several, attributes of earlier references win on
+/// duplicate ids like with [`Translator::add_reference`].
+pub fn merge_references(
+    references: impl IntoIterator<Item = HashMap<String, ObjectAttribute>>,
+) -> HashMap<String, ObjectAttribute> {
+    let mut oa_id_hashmap = HashMap::new();
+
+    for reference in references {
+        parse::merge_reference(&mut oa_id_hashmap, reference);
+    }
+
+    oa_id_hashmap
+}
+
+/// Find attributes and picklist options by full or partial id, name or label, best first.
+pub fn lookup(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    query: &str,
+    limit: usize,
+) -> Vec<LookupMatch> {
+    lookup::lookup(oa_id_hashmap, query, limit)
+}
+
+/// Payload of a search query written like a translated one, e.g.
+/// `(AND (equal Status "Open"))`, attributes and picklist options given by
+/// id, name or label.
+pub fn query_to_payload(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    query: &str,
+) -> Result<Value> {
+    query::parse(oa_id_hashmap, query)
+}
+
+/// List attributes, picklist options and nested forms of a reference.
+pub fn render_schema(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    filter: &SchemaFilter,
+    format: SchemaFormat,
+) -> Result<String> {
+    schema::render(oa_id_hashmap, filter, format)
+}
+
+/// Data dictionary of a reference, a section per object class with anchors.
+pub fn render_dictionary(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    title: &str,
+    format: DictionaryFormat,
+) -> String {
+    dictionary::render(oa_id_hashmap, title, format)
+}
+
+/// JSON Schema of entities or payloads of the object class described by a reference.
+pub fn generate_json_schema(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    target: JsonSchemaTarget,
+) -> Value {
+    codegen::json_schema::generate(oa_id_hashmap, target)
+}
+
+/// TypeScript interface of entities of the object class described by a reference,
+/// with picklist option types and a mapping from `oa_*` keys to names.
+pub fn generate_typescript(oa_id_hashmap: &HashMap<String, ObjectAttribute>, name: &str) -> String {
+    codegen::typescript::generate(oa_id_hashmap, name)
+}
+
+/// Rust source with a serde struct per object class, an enum per picklist and
+/// constants of attribute ids.
+pub fn generate_rust(oa_id_hashmap: &HashMap<String, ObjectAttribute>, name: &str) -> String {
+    codegen::rust::generate(oa_id_hashmap, name)
+}
+
+/// Serialize a parsed reference into a versioned, checksummed snapshot.
+pub fn build_snapshot(oa_id_hashmap: &HashMap<String, ObjectAttribute>) -> Result<String> {
+    snapshot::build(oa_id_hashmap)
+}
+
+/// Load a snapshot made by [`build_snapshot`], skipping the json:api parsing.
+pub fn load_snapshot(snapshot: &str) -> Result<HashMap<String, ObjectAttribute>> {
+    snapshot::load(snapshot)
+}
+
+/// Whether `content` looks like a snapshot rather than a raw reference.
+pub fn is_snapshot(content: &str) -> bool {
+    snapshot::is_snapshot(content)
+}
+
+/// Report missing, duplicated and orphaned entries of a raw reference.
+pub fn validate_reference(reference: &ObjectAttributesRaw) -> ReferenceReport {
+    validate::validate(reference)
+}
+
+/// Compare two parsed references, e.g. staging against production.
+pub fn diff_references(
+    old: &HashMap<String, ObjectAttribute>,
+    new: &HashMap<String, ObjectAttribute>,
+) -> ReferenceDiff {
+    diff::diff(old, new)
+}
+
 pub fn process_payload(
     oa_id_hashmap: &HashMap<String, ObjectAttribute>,
     payload: Payload,
 ) -> Result<HashMap<String, Value>> {
-    parse_payload(payload, oa_id_hashmap)
+    process_payload_with_options(oa_id_hashmap, payload, &Options::default())
+}
+
+pub fn process_payload_with_options(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    payload: Payload,
+    options: &Options,
+) -> Result<HashMap<String, Value>> {
+    parse_payload(payload, oa_id_hashmap, options)
 }
 
 pub fn process_response(
     oa_id_hashmap: &HashMap<String, ObjectAttribute>,
     response: Response,
 ) -> Result<Vec<HashMap<String, Value>>> {
-    parse_response(response, oa_id_hashmap)
+    process_response_with_options(oa_id_hashmap, response, &Options::default())
+}
+
+pub fn process_response_with_options(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    response: Response,
+    options: &Options,
+) -> Result<Vec<HashMap<String, Value>>> {
+    parse_response(response, oa_id_hashmap, options)
 }
 
 pub fn process_object_entity(
     oa_id_hashmap: &HashMap<String, ObjectAttribute>,
     entity: HashMap<String, Value>,
 ) -> Result<HashMap<String, Value>> {
-    parse_entity(entity, oa_id_hashmap)
+    process_object_entity_with_options(oa_id_hashmap, entity, &Options::default())
+}
+
+pub fn process_object_entity_with_options(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    entity: HashMap<String, Value>,
+    options: &Options,
+) -> Result<HashMap<String, Value>> {
+    parse_entity(entity, oa_id_hashmap, options)
 }
@@ -0,0 +1,194 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Serialize;
+
+use crate::{
+    ObjectAttribute,
+    parse::{PicklistOption, parse_oa_uuid},
+};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupKind {
+    ObjectAttribute,
+    PicklistOption,
+}
+
+/// Attribute or picklist option matching a lookup query, with its attribute as context.
+#[derive(Serialize)]
+pub struct LookupMatch {
+    pub kind: LookupKind,
+    /// Higher is better.
+    pub score: u32,
+    /// What matched, e.g. `id prefix` or `label`.
+    pub matched: &'static str,
+    pub object_attribute_id: String,
+    pub object_attribute_name: String,
+    pub object_attribute_label: String,
+    pub data_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub option_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub option_name: Option<String>,
+}
+
+impl Display for LookupMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attribute = format!(
+            "{} \"{}\" ({}) {}",
+            self.object_attribute_name,
+            self.object_attribute_label,
+            self.data_type,
+            self.object_attribute_id
+        );
+
+        match (&self.option_id, &self.option_name) {
+            (Some(id), Some(name)) => write!(
+                f,
+                "[{:>3}] option {name} {id}, by {}, of {attribute}",
+                self.score, self.matched
+            ),
+            _ => write!(f, "[{:>3}] {attribute}, by {}", self.score, self.matched),
+        }
+    }
+}
+
+/// Hex digits of an id, so `oa_019883f0_c110`, `019883f0-c110` and `019883f0c110` compare equal.
+fn normalize_id(id: &str) -> String {
+    parse_oa_uuid(id.trim())
+        .chars()
+        .filter(|c| *c != '-')
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Lowercase words, so `Type_Name` and `type name` compare equal.
+fn normalize_name(name: &str) -> String {
+    name.trim().replace(['_', '-'], " ").to_lowercase()
+}
+
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut text = text.chars();
+    query.chars().all(|c| text.any(|t| t == c))
+}
+
+fn score_id(query: &str, id: &str) -> Option<(u32, &'static str)> {
+    if query.len() < 4 || !query.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let id = normalize_id(id);
+
+    if id == query {
+        Some((100, "id"))
+    } else if id.starts_with(query) {
+        Some((90, "id prefix"))
+    } else if id.contains(query) {
+        Some((70, "id fragment"))
+    } else {
+        None
+    }
+}
+
+fn score_name(query: &str, name: &str, field: &'static str) -> Option<(u32, &'static str)> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let name = normalize_name(name);
+
+    if name == query {
+        Some((80, field))
+    } else if name.starts_with(query) {
+        Some((60, field))
+    } else if name.contains(query) {
+        Some((50, field))
+    } else if is_subsequence(query, &name) {
+        Some((30, field))
+    } else {
+        // Loose queries such as "the type field", scored by the share of words found.
+        let words: Vec<&str> = query.split_whitespace().collect();
+        let found = words.iter().filter(|word| name.contains(*word)).count();
+
+        (found > 0).then(|| (10 + (20 * found / words.len()) as u32, field))
+    }
+}
+
+/// Best score of an id, name and label.
+fn score(
+    id_query: &str,
+    name_query: &str,
+    id: &str,
+    name: &str,
+    label: &str,
+) -> Option<(u32, &'static str)> {
+    [
+        score_id(id_query, id),
+        score_name(name_query, name, "name"),
+        score_name(name_query, label, "label"),
+    ]
+    .into_iter()
+    .flatten()
+    .max_by_key(|(score, _)| *score)
+}
+
+fn lookup_match(
+    oa: &ObjectAttribute,
+    option: Option<&PicklistOption>,
+    (score, matched): (u32, &'static str),
+) -> LookupMatch {
+    LookupMatch {
+        kind: match option {
+            Some(_) => LookupKind::PicklistOption,
+            None => LookupKind::ObjectAttribute,
+        },
+        score,
+        matched,
+        object_attribute_id: oa.id.clone(),
+        object_attribute_name: oa.name.clone(),
+        object_attribute_label: oa.label().to_string(),
+        data_type: oa.data_type.to_string(),
+        option_id: option.map(|option| option.id.clone()),
+        option_name: option.map(|option| option.name.clone()),
+    }
+}
+
+/// Find attributes and picklist options by full or partial id, name or label, best first.
+pub fn lookup(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    query: &str,
+    limit: usize,
+) -> Vec<LookupMatch> {
+    let id_query = normalize_id(query);
+    let name_query = normalize_name(query);
+    let mut matches = Vec::new();
+
+    for oa in oa_id_hashmap.values() {
+        if let Some(score) = score(&id_query, &name_query, &oa.id, &oa.name, &oa.label) {
+            matches.push(lookup_match(oa, None, score));
+        }
+
+        for option in &oa.picklist_options {
+            // Options rank just below attributes with the same score.
+            if let Some((score, matched)) = score(
+                &id_query,
+                &name_query,
+                &option.id,
+                &option.name,
+                &option.label,
+            ) {
+                matches.push(lookup_match(oa, Some(option), (score - 1, matched)));
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.object_attribute_name.cmp(&b.object_attribute_name))
+            .then_with(|| a.option_name.cmp(&b.option_name))
+    });
+    matches.truncate(limit);
+
+    matches
+}
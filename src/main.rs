@@ -1,19 +1,406 @@
-use std::{fs, path::Path};
+mod cli;
 
-use anyhow::Result;
-use clap::{Args, Parser};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::{self, File},
+    io::{self, BufWriter, Cursor, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand};
 use json_replacer::{
-    preprocess_reference, process_object_entity, process_payload, process_response,
+    AttributeOrder, DictionaryFormat, JsonSchemaTarget, KeyStyle, ObjectAttribute,
+    ObjectAttributesRaw, SchemaFilter, SchemaFormat, StreamFormat, Timezone, Translated,
+    Translator, build_snapshot, diff_references, generate_json_schema, generate_rust,
+    generate_typescript, is_snapshot, load_snapshot, merge_references, preprocess_reference,
+    render_dictionary, render_schema, validate_reference,
+};
+use serde::Serialize;
+
+use cli::{
+    batch::{self, BatchFile, BatchReport, FileReport},
+    config::{Config, OutputFormat},
+    output,
+    repl::Repl,
+    watch::Watcher,
 };
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[command(flatten)]
     pub inclusive: Inclusive,
 
+    #[command(flatten)]
+    pub reference: ReferenceArgs,
+
+    #[command(flatten)]
+    pub translate: TranslateArgs,
+
+    /// Translate the response while reading it, printing entities as ndjson or a json array
+    #[arg(long, value_name = "FORMAT", requires = "response")]
+    pub stream: Option<StreamFormat>,
+
+    /// Write the result as json to a file instead of stdout, for a single payload, response or entity
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output files
+    #[arg(long)]
+    pub force: bool,
+
+    /// Translate again whenever an input file or the reference file changes
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Settings of the translation, shared by single and batch translation.
+#[derive(Args)]
+pub struct TranslateArgs {
+    /// Style of translated keys: label, name, name-type, id or label-id [default: name-type]
+    #[arg(short, long)]
+    pub key_style: Option<KeyStyle>,
+
+    /// Order of attributes: key, reference or id [default: key]
+    #[arg(long)]
+    pub order: Option<AttributeOrder>,
+
+    /// Output format [default: debug]
+    #[arg(short, long)]
+    pub format: Option<OutputFormat>,
+
+    /// Fail on unknown attributes and picklist options instead of warning
+    #[arg(long, overrides_with = "no_strict")]
+    pub strict: bool,
+
+    /// Warn on unknown attributes and picklist options, overriding the config
+    #[arg(long, overrides_with = "strict")]
+    pub no_strict: bool,
+
+    /// Redact values of an attribute, by id, name, label or `type:<data type>`, added to the config
+    #[arg(long, value_name = "SELECTOR")]
+    pub redact: Vec<String>,
+
+    /// Format values by data type, e.g. dates, percentages and booleans as yes/no
+    #[arg(long, overrides_with = "no_display")]
+    pub display: bool,
+
+    /// Pass values through as they are, overriding the config
+    #[arg(long, overrides_with = "display")]
+    pub no_display: bool,
+
+    /// Currency code of amounts, e.g. EUR
+    #[arg(long, value_name = "CODE")]
+    pub currency: Option<String>,
+
+    /// Show datetimes in a timezone, an IANA name such as Europe/Berlin or an offset like +08:00
+    #[arg(long, value_name = "TIMEZONE")]
+    pub timezone: Option<Timezone>,
+
+    /// Show changed values as {"value": ..., "original": ...}
+    #[arg(long, overrides_with = "no_keep_original")]
+    pub keep_original: bool,
+
+    /// Show changed values only, overriding the config
+    #[arg(long, overrides_with = "keep_original")]
+    pub no_keep_original: bool,
+}
+
+/// Setting of a flag and its `--no-` counterpart, `None` when neither is given.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+impl TranslateArgs {
+    /// Settings given as flags, they take precedence over config files.
+    fn config(&self) -> Config {
+        Config {
+            format: self.format,
+            key_style: self.key_style,
+            order: self.order,
+            strict: switch(self.strict, self.no_strict),
+            redact: (!self.redact.is_empty()).then(|| self.redact.clone()),
+            display: switch(self.display, self.no_display),
+            currency: self.currency.clone(),
+            timezone: self.timezone.clone(),
+            keep_original: switch(self.keep_original, self.no_keep_original),
+            ..Config::default()
+        }
+    }
+}
+
+/// Reference given as file, stored name or taken from the config.
+#[derive(Args, Clone)]
+pub struct ReferenceArgs {
     /// Path to reference file, should contain object_attributes api response.
-    pub reference_file: String,
+    pub reference_file: Option<String>,
+
+    /// Name of a stored reference, see `ref add`. Defaults to the configured references.
+    #[arg(long = "ref", value_name = "NAME", conflicts_with = "reference_file")]
+    pub reference_name: Option<String>,
+}
+
+impl ReferenceArgs {
+    fn load(self, config: &Config) -> Result<HashMap<String, ObjectAttribute>> {
+        resolve_reference(self.reference_file, self.reference_name, config)
+    }
+
+    /// Files the reference is read from, none for stored references and content.
+    fn paths(&self, config: &Config) -> Vec<PathBuf> {
+        match (&self.reference_file, &self.reference_name) {
+            (Some(reference_file), _) => vec![PathBuf::from(reference_file)],
+            (None, None) => config.references.clone().unwrap_or_default(),
+            (None, Some(_)) => Vec::new(),
+        }
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect()
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage references stored under a name.
+    Ref {
+        #[command(subcommand)]
+        command: RefCommand,
+    },
+
+    /// Print the effective configuration and the files it was read from.
+    Config,
+
+    /// List attributes, picklist options and nested forms of a reference.
+    Schema {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Only attributes of this data type, e.g. picklist
+        #[arg(short = 't', long = "type", value_name = "DATA_TYPE")]
+        data_type: Option<String>,
+
+        /// Only attributes whose name or label contains this text
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Output format: table, markdown or json
+        #[arg(short, long, default_value_t = SchemaFormat::Table)]
+        format: SchemaFormat,
+    },
+
+    /// Export a reference as a data dictionary document.
+    Dictionary {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Output format: markdown or html
+        #[arg(short, long, default_value_t = DictionaryFormat::Markdown)]
+        format: DictionaryFormat,
+
+        /// Title of the document
+        #[arg(short, long, default_value = "Data dictionary")]
+        title: String,
+
+        /// Path to write the document to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Generate schemas and types from a reference.
+    Codegen {
+        #[command(subcommand)]
+        command: CodegenCommand,
+    },
+
+    /// Translate many payload, response and entity files with one reference.
+    Batch {
+        /// Files, directories of json files or glob patterns such as 'captures/**/*.json'
+        #[arg(required = true, value_name = "INPUT")]
+        inputs: Vec<String>,
+
+        /// Path to reference file, defaults to the stored or configured references
+        #[arg(long = "reference", value_name = "FILE")]
+        reference_file: Option<String>,
+
+        /// Name of a stored reference, see `ref add`
+        #[arg(long = "ref", value_name = "NAME", conflicts_with = "reference_file")]
+        reference_name: Option<String>,
+
+        /// Directory to write each translation to, mirroring the inputs
+        #[arg(short, long, value_name = "DIR", conflicts_with_all = ["report", "suffix"])]
+        out_dir: Option<PathBuf>,
+
+        /// File to write all translations to as one json report
+        #[arg(long, value_name = "FILE", conflicts_with = "suffix")]
+        report: Option<PathBuf>,
+
+        /// Write each translation next to its file, e.g. .translated.json for response.translated.json
+        #[arg(long)]
+        suffix: Option<String>,
+
+        /// Overwrite existing output files
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        translate: TranslateArgs,
+    },
+
+    /// Translate pasted json, look up attributes and convert queries interactively.
+    Repl {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        #[command(flatten)]
+        translate: TranslateArgs,
+    },
+
+    /// Find attributes and picklist options by id fragment, name or label.
+    Lookup {
+        /// Full or partial id, with or without `oa_` prefix, or a name or label
+        query: String,
+
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Maximum number of matches
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+
+        /// Print the matches as json
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check a reference for missing, duplicated and orphaned entries.
+    ValidateReference {
+        /// Path to reference file, or reference content
+        reference_file: String,
+    },
+
+    /// Show attributes and picklist options changed between two references.
+    Diff {
+        /// Path to old reference file, or reference content
+        old_reference_file: String,
+
+        /// Path to new reference file, or reference content
+        new_reference_file: String,
+
+        /// Print the differences as json
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compile a reference into a snapshot that loads faster.
+    Snapshot {
+        /// Path to reference file, or reference content
+        reference_file: String,
+
+        /// Path to write the snapshot to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RefCommand {
+    /// Import a reference under a name, e.g. `tenant-a/orders`.
+    Add {
+        name: String,
+
+        /// Path to reference file, or reference content
+        reference_file: String,
+
+        /// Replace a reference already stored under this name
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// List stored references, the default one is marked with `*`.
+    List,
+
+    /// Remove a stored reference.
+    Remove { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum CodegenCommand {
+    /// JSON Schema of object entities or payloads.
+    JsonSchema {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Document to validate: entity or payload
+        #[arg(short, long, default_value_t = JsonSchemaTarget::Entity)]
+        target: JsonSchemaTarget,
+
+        /// Path to write the schema to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// TypeScript interfaces of object entities.
+    Ts {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Name of the entity interface
+        #[arg(short, long, default_value = "ObjectEntity")]
+        name: String,
+
+        /// Path to write the types to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Rust structs, picklist enums and attribute id constants.
+    Rust {
+        #[command(flatten)]
+        reference: ReferenceArgs,
+
+        /// Name of the entity struct
+        #[arg(short, long, default_value = "ObjectEntity")]
+        name: String,
+
+        /// Path to write the source to, defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(Args)]
@@ -36,6 +423,17 @@ fn pretty_print(title: &str, count: usize) {
     println!("\n{:#^width$}\n", format!(" {title} "), width = count);
 }
 
+fn print_result<T: Debug + Serialize>(title: &str, result: &T, format: OutputFormat) -> Result<()> {
+    pretty_print(title, 80);
+
+    match format {
+        OutputFormat::Debug => println!("{result:#?}"),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+    }
+
+    Ok(())
+}
+
 fn get_content(input: String) -> Result<String> {
     let trimmed_input = input.trim();
 
@@ -53,39 +451,660 @@ fn get_content(input: String) -> Result<String> {
     Ok(input)
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Reader of a file, or of the content itself, like `get_content`.
+fn get_reader(input: String) -> Result<Box<dyn Read>> {
+    let trimmed_input = input.trim();
+    let is_json = trimmed_input.starts_with('{') || trimmed_input.starts_with('[');
+    let path = Path::new(&input);
+
+    if !is_json && path.exists() {
+        // Treat as file.
+        return Ok(Box::new(File::open(path)?));
+    }
+
+    Ok(Box::new(Cursor::new(input)))
+}
+
+fn load_reference(reference_file: String) -> Result<HashMap<String, ObjectAttribute>> {
+    let reference_content = get_content(reference_file)?;
+
+    if is_snapshot(&reference_content) {
+        return load_snapshot(&reference_content);
+    }
 
-    let reference_content = get_content(cli.reference_file)?;
     let reference = serde_json::from_str(&reference_content)?;
-    let oa_id_hashmap = preprocess_reference(reference)?;
+    preprocess_reference(reference)
+}
 
-    if let Some(payload) = cli.inclusive.payload {
-        let payload_content = get_content(payload)?;
-        let payload = serde_json::from_str(&payload_content)?;
-        let result = process_payload(&oa_id_hashmap, payload)?;
+/// Reference from the positional file, `--ref`, the configured references or
+/// the configured default stored reference, in this order.
+fn resolve_reference(
+    reference_file: Option<String>,
+    reference_name: Option<String>,
+    config: &Config,
+) -> Result<HashMap<String, ObjectAttribute>> {
+    if let Some(reference_file) = reference_file {
+        return load_reference(reference_file);
+    }
 
-        pretty_print("payload", 80);
-        println!("{result:#?}");
+    if reference_name.is_none()
+        && let Some(references) = config.references.as_ref().filter(|paths| !paths.is_empty())
+    {
+        return Ok(merge_references(
+            references
+                .iter()
+                .map(|reference| load_reference(reference.to_string_lossy().into_owned()))
+                .collect::<Result<Vec<_>>>()?,
+        ));
     }
 
-    if let Some(response) = cli.inclusive.response {
-        let response_content = get_content(response)?;
-        let response = serde_json::from_str(&response_content)?;
-        let result = process_response(&oa_id_hashmap, response)?;
+    let Some(name) = reference_name.or(config.default_ref.clone()) else {
+        bail!("no reference given, pass a reference file, --ref or set one in the config");
+    };
+
+    load_snapshot(&cli::store::Store::open()?.get(&name)?)
+}
+
+fn schema(
+    reference: ReferenceArgs,
+    filter: SchemaFilter,
+    format: SchemaFormat,
+) -> Result<ExitCode> {
+    let (config, _) = cli::config::load()?;
+    let oa_id_hashmap = reference.load(&config)?;
 
-        pretty_print("response", 80);
-        println!("{result:#?}");
+    println!("{}", render_schema(&oa_id_hashmap, &filter, format)?);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn lookup(query: String, reference: ReferenceArgs, limit: usize, json: bool) -> Result<ExitCode> {
+    let (config, _) = cli::config::load()?;
+    let oa_id_hashmap = reference.load(&config)?;
+    let matches = json_replacer::lookup(&oa_id_hashmap, &query, limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+    } else if matches.is_empty() {
+        println!("No matches found.");
+    } else {
+        for lookup_match in matches {
+            println!("{lookup_match}");
+        }
     }
 
-    if let Some(object_entity) = cli.inclusive.object_entity {
-        let entity_content = get_content(object_entity)?;
-        let entity = serde_json::from_str(&entity_content)?;
-        let result = process_object_entity(&oa_id_hashmap, entity)?;
+    Ok(ExitCode::SUCCESS)
+}
 
-        pretty_print("entity", 80);
-        println!("{result:#?}");
+/// Where a batch writes its translations, printed when none is given.
+struct BatchOutput {
+    out_dir: Option<PathBuf>,
+    report: Option<PathBuf>,
+    suffix: Option<String>,
+    force: bool,
+}
+
+impl BatchOutput {
+    /// File to write the translation of `file` to.
+    fn destination(&self, file: &BatchFile) -> Option<PathBuf> {
+        match (&self.out_dir, &self.suffix) {
+            (Some(out_dir), _) => Some(out_dir.join(&file.relative)),
+            (_, Some(suffix)) => Some(output::companion(&file.path, suffix)),
+            _ => None,
+        }
+    }
+}
+
+/// Translation of one file, written to `destination` when given.
+fn batch_file<'r>(
+    translator: &'r Translator,
+    file: &BatchFile,
+    destination: Option<&Path>,
+    force: bool,
+) -> Result<Translated<'r>> {
+    let content = fs::read_to_string(&file.path)?;
+    let translated = translator.translate_value(&serde_json::from_str(&content)?)?;
+
+    if let Some(destination) = destination {
+        output::write(
+            destination,
+            &(serde_json::to_string_pretty(&translated)? + "\n"),
+            force,
+        )?;
+    }
+
+    Ok(translated)
+}
+
+fn batch(
+    inputs: Vec<String>,
+    reference: ReferenceArgs,
+    output: BatchOutput,
+    args: TranslateArgs,
+) -> Result<ExitCode> {
+    let config = cli::config::load()?.0.merge(args.config());
+    let translator = Translator::builder()
+        .reference(reference.load(&config)?)
+        .options(config.options())
+        .build();
+    let format = config.format.unwrap_or_default();
+
+    let mut files = batch::collect(&inputs)?;
+
+    // Translations of an earlier run are not translated again.
+    if let Some(suffix) = &output.suffix {
+        files.retain(|file| !file.path.to_string_lossy().ends_with(suffix.as_str()));
+    }
+
+    if let Some(out_dir) = &output.out_dir {
+        batch::skip_dir(&mut files, out_dir);
+    }
+
+    let mut destinations = HashMap::new();
+
+    for file in &files {
+        if let Some(destination) = output.destination(file)
+            && let Some(other) = destinations.insert(destination.clone(), &file.path)
+        {
+            bail!(
+                "{} and {} would both be written to {}",
+                other.display(),
+                file.path.display(),
+                destination.display()
+            );
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut failed = 0;
+
+    for file in &files {
+        let path = file.path.display().to_string();
+        let destination = output.destination(file);
+
+        match batch_file(&translator, file, destination.as_deref(), output.force) {
+            Ok(translated) => {
+                match &destination {
+                    Some(destination) => eprintln!(
+                        "ok      {path} -> {} ({})",
+                        destination.display(),
+                        translated.kind()
+                    ),
+                    None => eprintln!("ok      {path} ({})", translated.kind()),
+                }
+
+                if output.report.is_some() {
+                    results.push(FileReport::translated(path, translated));
+                } else if destination.is_none() {
+                    print_result(&path, &translated, format)?;
+                }
+            }
+            Err(error) => {
+                eprintln!("failed  {path}: {error:#}");
+                failed += 1;
+
+                if output.report.is_some() {
+                    results.push(FileReport::failed(path, &error));
+                }
+            }
+        }
+    }
+
+    eprintln!(
+        "{} files, {} translated, {failed} failed",
+        files.len(),
+        files.len() - failed
+    );
+
+    if let Some(report) = &output.report {
+        let report_content = serde_json::to_string_pretty(&BatchReport {
+            files: files.len(),
+            failed,
+            results,
+        })?;
+
+        output::write(report, &(report_content + "\n"), output.force)?;
+    }
+
+    Ok(if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn repl(reference: ReferenceArgs, args: TranslateArgs) -> Result<ExitCode> {
+    let config = cli::config::load()?.0.merge(args.config());
+    let translator = Translator::builder()
+        .reference(reference.load(&config)?)
+        .options(config.options())
+        .build();
+
+    Repl::new(translator, config.format.unwrap_or_default()).run()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn dictionary(
+    reference: ReferenceArgs,
+    format: DictionaryFormat,
+    title: String,
+    output: Option<String>,
+    force: bool,
+) -> Result<ExitCode> {
+    let (config, _) = cli::config::load()?;
+    let oa_id_hashmap = reference.load(&config)?;
+    let dictionary = render_dictionary(&oa_id_hashmap, &title, format);
+
+    match output {
+        Some(path) => output::write(Path::new(&path), &dictionary, force)?,
+        None => print!("{dictionary}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn codegen(command: CodegenCommand) -> Result<ExitCode> {
+    let (config, _) = cli::config::load()?;
+
+    let (generated, output, force) = match command {
+        CodegenCommand::JsonSchema {
+            reference,
+            target,
+            output,
+            force,
+        } => {
+            let oa_id_hashmap = reference.load(&config)?;
+            let schema = generate_json_schema(&oa_id_hashmap, target);
+
+            (serde_json::to_string_pretty(&schema)?, output, force)
+        }
+        CodegenCommand::Ts {
+            reference,
+            name,
+            output,
+            force,
+        } => {
+            let oa_id_hashmap = reference.load(&config)?;
+
+            (generate_typescript(&oa_id_hashmap, &name), output, force)
+        }
+        CodegenCommand::Rust {
+            reference,
+            name,
+            output,
+            force,
+        } => {
+            let oa_id_hashmap = reference.load(&config)?;
+
+            (generate_rust(&oa_id_hashmap, &name), output, force)
+        }
+    };
+
+    match output {
+        Some(path) => output::write(Path::new(&path), &generated, force)?,
+        None => println!("{generated}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_config() -> Result<ExitCode> {
+    let (config, sources) = cli::config::load()?;
+
+    for source in sources {
+        println!("# {}", source.display());
+    }
+
+    print!("{}", toml::to_string(&config)?);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn reference_command(command: RefCommand) -> Result<ExitCode> {
+    let store = cli::store::Store::open()?;
+
+    match command {
+        RefCommand::Add {
+            name,
+            reference_file,
+            force,
+        } => {
+            let oa_id_hashmap = load_reference(reference_file)?;
+            let path = store.add(&name, &build_snapshot(&oa_id_hashmap)?, force)?;
+
+            println!("Stored {name} at {}", path.display());
+        }
+        RefCommand::List => {
+            let default_ref = cli::config::load()?.0.default_ref;
+
+            for name in store.list()? {
+                let marker = if default_ref.as_ref() == Some(&name) {
+                    "*"
+                } else {
+                    " "
+                };
+
+                println!("{marker} {name}");
+            }
+        }
+        RefCommand::Remove { name } => {
+            store.remove(&name)?;
+
+            println!("Removed {name}");
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn snapshot(reference_file: String, output: Option<String>, force: bool) -> Result<ExitCode> {
+    let oa_id_hashmap = load_reference(reference_file)?;
+    let snapshot = build_snapshot(&oa_id_hashmap)?;
+
+    match output {
+        Some(path) => output::write(Path::new(&path), &snapshot, force)?,
+        None => println!("{snapshot}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(old_reference_file: String, new_reference_file: String, json: bool) -> Result<ExitCode> {
+    let old = load_reference(old_reference_file)?;
+    let new = load_reference(new_reference_file)?;
+    let diff = diff_references(&old, &new);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!("{diff}");
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(reference_file: String) -> Result<ExitCode> {
+    let reference_content = get_content(reference_file)?;
+    let reference: ObjectAttributesRaw = serde_json::from_str(&reference_content)?;
+    let report = validate_reference(&reference);
+
+    println!("{report}");
+
+    if report.is_valid() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Print a result, or write it as json to `output`.
+fn emit<T: Debug + Serialize>(
+    title: &str,
+    result: &T,
+    format: OutputFormat,
+    output: Option<&Path>,
+    force: bool,
+) -> Result<()> {
+    match output {
+        Some(output) => output::write(
+            output,
+            &(serde_json::to_string_pretty(result)? + "\n"),
+            force,
+        ),
+        None => print_result(title, result, format),
+    }
+}
+
+/// Translate the inputs given as flags.
+fn run_translation(
+    cli: &Cli,
+    translator: &Translator,
+    format: OutputFormat,
+    force: bool,
+) -> Result<()> {
+    let output = cli.output.as_deref();
+
+    if let Some(payload) = &cli.inclusive.payload {
+        let payload_content = get_content(payload.clone())?;
+        let result = translator.payload_str(&payload_content)?;
+
+        emit("payload", &result, format, output, force)?;
+    }
+
+    if let (Some(response), Some(stream)) = (&cli.inclusive.response, cli.stream) {
+        let reader = get_reader(response.clone())?;
+
+        match output {
+            Some(output) => {
+                let file = output::create(output, force)?;
+                translator.write_response(reader, BufWriter::new(file), stream)?;
+            }
+            None => {
+                let stdout = io::stdout().lock();
+                translator.write_response(reader, BufWriter::new(stdout), stream)?;
+            }
+        }
+    } else if let Some(response) = &cli.inclusive.response {
+        let response_content = get_content(response.clone())?;
+        let result = translator.response_str(&response_content)?;
+
+        emit("response", &result, format, output, force)?;
+    }
+
+    if let Some(object_entity) = &cli.inclusive.object_entity {
+        let entity_content = get_content(object_entity.clone())?;
+        let result = translator.entity_str(&entity_content)?;
+
+        emit("entity", &result, format, output, force)?;
     }
 
     Ok(())
 }
+
+fn translate(cli: Cli) -> Result<ExitCode> {
+    let inputs = [
+        &cli.inclusive.payload,
+        &cli.inclusive.response,
+        &cli.inclusive.object_entity,
+    ];
+
+    if cli.output.is_some() && inputs.iter().filter(|input| input.is_some()).count() > 1 {
+        bail!("--output takes a single payload, response or entity, see `batch` for more");
+    }
+
+    let config = cli::config::load()?.0.merge(cli.translate.config());
+
+    if cli.watch {
+        return watch(&cli, &config);
+    }
+
+    let translator = Translator::builder()
+        .reference(cli.reference.clone().load(&config)?)
+        .options(config.options())
+        .build();
+
+    run_translation(
+        &cli,
+        &translator,
+        config.format.unwrap_or_default(),
+        cli.force,
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Translate the inputs again whenever they or the reference file change,
+/// the reference is only read again when it changed.
+fn watch(cli: &Cli, config: &Config) -> Result<ExitCode> {
+    let inputs = [
+        &cli.inclusive.payload,
+        &cli.inclusive.response,
+        &cli.inclusive.object_entity,
+    ]
+    .into_iter()
+    .flatten()
+    .map(|input| {
+        let path = PathBuf::from(input);
+
+        if !path.is_file() {
+            bail!("--watch needs input files, {input} is not a file");
+        }
+
+        Ok(path)
+    })
+    .collect::<Result<Vec<PathBuf>>>()?;
+
+    let reference_paths = cli.reference.paths(config);
+    let load_translator = || -> Result<Translator> {
+        Ok(Translator::builder()
+            .reference(
+                cli.reference
+                    .clone()
+                    .load(config)
+                    .context("failed to load the reference")?,
+            )
+            .options(config.options())
+            .build())
+    };
+
+    let mut translator = load_translator()?;
+    let files = inputs.len() + reference_paths.len();
+    let mut watcher = Watcher::new(inputs.into_iter().chain(reference_paths.clone()));
+    let format = config.format.unwrap_or_default();
+    // Output files written by an earlier run are ours to replace.
+    let mut force = cli.force;
+    let mut notice = None;
+
+    loop {
+        // Clear the screen and move to its top, output piped to a file is kept.
+        if io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+            io::stdout().flush()?;
+        }
+
+        if let Some(notice) = notice.take() {
+            eprintln!("{notice}");
+        }
+
+        match run_translation(cli, &translator, format, force) {
+            Ok(()) => force = true,
+            Err(error) => eprintln!("Error: {error:#}"),
+        }
+
+        eprintln!("\nWatching {files} files, press Ctrl-C to stop");
+
+        let changed = watcher.wait();
+
+        if reference_paths.iter().any(|path| changed.contains(path)) {
+            match load_translator() {
+                Ok(reloaded) => translator = reloaded,
+                Err(error) => {
+                    notice = Some(format!(
+                        "Error: {error:#}\nKeeping the reference loaded before"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn main() -> Result<ExitCode> {
+    let mut cli = Cli::parse();
+
+    match cli.command.take() {
+        Some(Command::Ref { command }) => reference_command(command),
+        Some(Command::Config) => print_config(),
+        Some(Command::Dictionary {
+            reference,
+            format,
+            title,
+            output,
+            force,
+        }) => dictionary(reference, format, title, output, force),
+        Some(Command::Codegen { command }) => codegen(command),
+        Some(Command::Repl {
+            reference,
+            translate,
+        }) => repl(reference, translate),
+        Some(Command::Batch {
+            inputs,
+            reference_file,
+            reference_name,
+            out_dir,
+            report,
+            suffix,
+            force,
+            translate,
+        }) => batch(
+            inputs,
+            ReferenceArgs {
+                reference_file,
+                reference_name,
+            },
+            BatchOutput {
+                out_dir,
+                report,
+                suffix,
+                force,
+            },
+            translate,
+        ),
+        Some(Command::Lookup {
+            query,
+            reference,
+            limit,
+            json,
+        }) => lookup(query, reference, limit, json),
+        Some(Command::Schema {
+            reference,
+            data_type,
+            name,
+            format,
+        }) => schema(reference, SchemaFilter { data_type, name }, format),
+        Some(Command::ValidateReference { reference_file }) => validate(reference_file),
+        Some(Command::Diff {
+            old_reference_file,
+            new_reference_file,
+            json,
+        }) => diff(old_reference_file, new_reference_file, json),
+        Some(Command::Snapshot {
+            reference_file,
+            output,
+            force,
+        }) => snapshot(reference_file, output, force),
+        None => translate(cli),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Parser)]
+    struct Flags {
+        #[command(flatten)]
+        translate: TranslateArgs,
+    }
+
+    #[test]
+    fn negated_flags_override_config() {
+        let file = Config {
+            strict: Some(true),
+            display: Some(true),
+            ..Config::default()
+        };
+
+        let flags = Flags::parse_from(["json-replacer", "--no-strict"]);
+        let config = file.merge(flags.translate.config());
+
+        assert_eq!(config.strict, Some(false));
+        assert_eq!(config.display, Some(true));
+        assert_eq!(config.keep_original, None);
+
+        // The last of a flag and its negation wins.
+        let flags = Flags::parse_from(["json-replacer", "--no-display", "--display"]);
+
+        assert_eq!(flags.translate.config().display, Some(true));
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{Error, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{FormatterTarget, Formatters, Timezone, ValueFormatter};
+
+/// How translated object attributes are keyed in the output.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum KeyStyle {
+    /// `Type Name`
+    Label,
+    /// `Type_Name`
+    Name,
+    /// `Type_Name (picklist)`
+    #[default]
+    NameType,
+    /// `019883f0-c110-7bc5-854e-26a7135a9ec0`
+    Id,
+    /// `Type Name [019883f0-c110-7bc5-854e-26a7135a9ec0]`
+    LabelId,
+}
+
+impl KeyStyle {
+    /// Whether picklist options should be shown by label rather than name.
+    pub fn prefers_label(&self) -> bool {
+        matches!(self, KeyStyle::Label | KeyStyle::LabelId)
+    }
+}
+
+impl FromStr for KeyStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "label" => Ok(KeyStyle::Label),
+            "name" => Ok(KeyStyle::Name),
+            "name-type" => Ok(KeyStyle::NameType),
+            "id" => Ok(KeyStyle::Id),
+            "label-id" => Ok(KeyStyle::LabelId),
+            _ => bail!(
+                "unknown key style: {s}, expected one of label, name, name-type, id, label-id"
+            ),
+        }
+    }
+}
+
+impl TryFrom<String> for KeyStyle {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyStyle> for String {
+    fn from(value: KeyStyle) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for KeyStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyStyle::Label => write!(f, "label"),
+            KeyStyle::Name => write!(f, "name"),
+            KeyStyle::NameType => write!(f, "name-type"),
+            KeyStyle::Id => write!(f, "id"),
+            KeyStyle::LabelId => write!(f, "label-id"),
+        }
+    }
+}
+
+/// Order of the attributes of translated entities.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AttributeOrder {
+    /// Sorted by translated key.
+    #[default]
+    Key,
+    /// In the order attributes are listed in the reference.
+    Reference,
+    /// Sorted by attribute id, ids are time ordered so this is creation order.
+    Id,
+}
+
+impl FromStr for AttributeOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "key" => Ok(AttributeOrder::Key),
+            "reference" => Ok(AttributeOrder::Reference),
+            "id" => Ok(AttributeOrder::Id),
+            _ => bail!("unknown attribute order: {s}, expected one of key, reference, id"),
+        }
+    }
+}
+
+impl TryFrom<String> for AttributeOrder {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<AttributeOrder> for String {
+    fn from(value: AttributeOrder) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for AttributeOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeOrder::Key => write!(f, "key"),
+            AttributeOrder::Reference => write!(f, "reference"),
+            AttributeOrder::Id => write!(f, "id"),
+        }
+    }
+}
+
+/// Options controlling how inputs are translated.
+///
+/// Attributes in `redact` and `templates` are selected by id, name, label
+/// or `type:<data type>`, e.g. `type:encrypted_string`.
+#[derive(Clone, Default)]
+pub struct Options {
+    pub key_style: KeyStyle,
+    /// Order of attributes in entities returned by a [`crate::Translator`].
+    pub order: AttributeOrder,
+    /// Fail on unknown attributes and picklist options instead of warning.
+    pub strict: bool,
+    /// Attributes whose values are replaced by `[redacted]`.
+    pub redact: Vec<String>,
+    /// Selector and template pairs, `{value}` is replaced by the translated value.
+    pub templates: Vec<(String, String)>,
+    /// Format values by data type, e.g. booleans as yes/no and addresses on one line.
+    pub display: bool,
+    /// Currency code of amounts, e.g. `EUR`.
+    pub currency: Option<String>,
+    /// Timezone datetimes and relative date conditions are shown in, UTC when unset.
+    pub timezone: Option<Timezone>,
+    /// Show changed values as `{"value": ..., "original": ...}`: picklist
+    /// options, formatted and templated values, but not nested forms.
+    pub keep_original: bool,
+    /// Custom value formatters, they take precedence over the built-in translation.
+    pub formatters: Formatters,
+}
+
+impl Options {
+    /// Render values of an attribute or data type with `formatter`.
+    pub fn register_formatter(
+        &mut self,
+        target: FormatterTarget,
+        formatter: impl ValueFormatter + 'static,
+    ) -> &mut Self {
+        self.formatters.register(target, formatter);
+        self
+    }
+}
@@ -3,21 +3,182 @@ pub mod payload;
 pub mod reference;
 pub mod response;
 
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet, hash_map::Entry},
+    fmt::Display,
+};
 
-use serde::Deserialize;
-use serde_json::Value;
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-#[derive(Deserialize)]
+use crate::{
+    format::format_value,
+    options::{AttributeOrder, KeyStyle, Options},
+};
+
+/// Replacement of redacted values.
+const REDACTED: &str = "[redacted]";
+
+#[derive(Deserialize, Serialize)]
 pub struct ObjectAttribute {
-    data_type: ObjectAttributeDataType,
-    name: String,
-    picklist_options: Vec<PicklistOption>,
+    pub(crate) id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) object_class_id: Option<String>,
+    pub(crate) data_type: ObjectAttributeDataType,
+    pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) label: String,
+    /// Options in their configured order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) picklist_options: Vec<PicklistOption>,
+    /// Attribute ids of a nested form.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) children: Vec<String>,
+    /// Position of the attribute in its reference.
+    pub(crate) position: usize,
+    /// Option id to position in `picklist_options`.
+    #[serde(skip)]
+    option_index: HashMap<String, usize>,
+    /// Option label and name to position in `picklist_options`.
+    #[serde(skip)]
+    label_index: HashMap<String, usize>,
 }
 
-#[derive(Deserialize, PartialEq, Copy, Clone)]
-#[serde(rename_all = "snake_case")]
-enum ObjectAttributeDataType {
+impl ObjectAttribute {
+    pub(crate) fn new(
+        id: String,
+        object_class_id: Option<String>,
+        data_type: ObjectAttributeDataType,
+        name: String,
+        label: String,
+        picklist_options: Vec<PicklistOption>,
+        children: Vec<String>,
+    ) -> Self {
+        let mut oa = ObjectAttribute {
+            id,
+            object_class_id,
+            data_type,
+            name,
+            label,
+            picklist_options,
+            children,
+            position: 0,
+            option_index: HashMap::new(),
+            label_index: HashMap::new(),
+        };
+        oa.index_options();
+        oa
+    }
+
+    /// Build the option lookup indices, the first option wins on duplicates.
+    pub(crate) fn index_options(&mut self) {
+        self.option_index = HashMap::with_capacity(self.picklist_options.len());
+        self.label_index = HashMap::with_capacity(self.picklist_options.len());
+
+        for (index, option) in self.picklist_options.iter().enumerate() {
+            self.option_index.entry(option.id.clone()).or_insert(index);
+            self.label_index.entry(option.name.clone()).or_insert(index);
+
+            if !option.label.is_empty() {
+                self.label_index
+                    .entry(option.label.clone())
+                    .or_insert(index);
+            }
+        }
+    }
+
+    /// Whether `selector` is the id, name or label of the attribute, or `type:<data type>`.
+    pub(crate) fn matches(&self, selector: &str) -> bool {
+        match selector.strip_prefix("type:") {
+            Some(data_type) => self.data_type.to_string() == data_type,
+            None => selector == self.id || selector == self.name || selector == self.label,
+        }
+    }
+
+    pub(crate) fn is_redacted(&self, options: &Options) -> bool {
+        options.redact.iter().any(|selector| self.matches(selector))
+    }
+
+    /// Render `value` with the first matching template, `{value}` is the placeholder.
+    pub(crate) fn apply_templates(&self, value: Value, options: &Options) -> Value {
+        let Some((_, template)) = options
+            .templates
+            .iter()
+            .find(|(selector, _)| self.matches(selector))
+        else {
+            return value;
+        };
+
+        let value = match value {
+            Value::Null => return value,
+            Value::String(value) => value,
+            value => value.to_string(),
+        };
+
+        Value::String(template.replace("{value}", &value))
+    }
+
+    /// Picklist option by id.
+    pub(crate) fn option(&self, id: &str) -> Option<&PicklistOption> {
+        self.option_index
+            .get(id)
+            .map(|&index| &self.picklist_options[index])
+    }
+
+    /// Picklist option by label or name.
+    pub(crate) fn option_by_label(&self, label: &str) -> Option<&PicklistOption> {
+        self.label_index
+            .get(label)
+            .map(|&index| &self.picklist_options[index])
+    }
+
+    /// Attribute id, without `oa_` prefix.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn data_type(&self) -> &ObjectAttributeDataType {
+        &self.data_type
+    }
+
+    /// Label of the attribute, falls back to name when the label is empty.
+    pub fn label(&self) -> &str {
+        if self.label.is_empty() {
+            &self.name
+        } else {
+            &self.label
+        }
+    }
+
+    /// Key of the attribute in translated entities, borrowed unless the key
+    /// style combines parts.
+    fn key(&self, key_style: KeyStyle) -> Cow<'_, str> {
+        match key_style {
+            KeyStyle::NameType => format!("{} ({})", self.name, self.data_type).into(),
+            _ => self.display_name(key_style),
+        }
+    }
+
+    /// Name of the attribute in search queries and attribute lists.
+    fn display_name(&self, key_style: KeyStyle) -> Cow<'_, str> {
+        match key_style {
+            KeyStyle::Label => self.label().into(),
+            KeyStyle::Name | KeyStyle::NameType => self.name.as_str().into(),
+            KeyStyle::Id => self.id.as_str().into(),
+            KeyStyle::LabelId => format!("{} [{}]", self.label(), self.id).into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+#[serde(from = "String", into = "String")]
+pub enum ObjectAttributeDataType {
     Address,
     Boolean,
     Currency,
@@ -39,6 +200,43 @@ enum ObjectAttributeDataType {
     Text,
     Textarea,
     Richtextarea,
+    /// Data type not known by this version, values are passed through.
+    Unknown(String),
+}
+
+impl From<String> for ObjectAttributeDataType {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "address" => ObjectAttributeDataType::Address,
+            "boolean" => ObjectAttributeDataType::Boolean,
+            "currency" => ObjectAttributeDataType::Currency,
+            "date" => ObjectAttributeDataType::Date,
+            "datetime" => ObjectAttributeDataType::Datetime,
+            "email" => ObjectAttributeDataType::Email,
+            "encrypted_string" => ObjectAttributeDataType::EncryptedString,
+            "file" => ObjectAttributeDataType::File,
+            "float" => ObjectAttributeDataType::Float,
+            "id" => ObjectAttributeDataType::Id,
+            "integer" => ObjectAttributeDataType::Integer,
+            "nested_form" => ObjectAttributeDataType::NestedForm,
+            "number" => ObjectAttributeDataType::Number,
+            "percent" => ObjectAttributeDataType::Percent,
+            "phone" => ObjectAttributeDataType::Phone,
+            "picklist" => ObjectAttributeDataType::Picklist,
+            "reference" => ObjectAttributeDataType::Reference,
+            "string" => ObjectAttributeDataType::String,
+            "text" => ObjectAttributeDataType::Text,
+            "textarea" => ObjectAttributeDataType::Textarea,
+            "richtextarea" | "rich_textarea" => ObjectAttributeDataType::Richtextarea,
+            _ => ObjectAttributeDataType::Unknown(value),
+        }
+    }
+}
+
+impl From<ObjectAttributeDataType> for String {
+    fn from(value: ObjectAttributeDataType) -> Self {
+        value.to_string()
+    }
 }
 
 impl Display for ObjectAttributeDataType {
@@ -65,14 +263,47 @@ impl Display for ObjectAttributeDataType {
             ObjectAttributeDataType::Text => write!(f, "text"),
             ObjectAttributeDataType::Textarea => write!(f, "textarea"),
             ObjectAttributeDataType::Richtextarea => write!(f, "rich_textarea"),
+            ObjectAttributeDataType::Unknown(data_type) => write!(f, "{data_type}"),
         }
     }
 }
 
-#[derive(Deserialize)]
-struct PicklistOption {
-    id: String,
-    name: String,
+#[derive(Deserialize, Serialize, Clone)]
+pub(crate) struct PicklistOption {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) label: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) disabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) position: Option<i64>,
+    /// Remaining option attributes, e.g. colors.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub(crate) extra: Map<String, Value>,
+}
+
+impl PicklistOption {
+    /// Label or name of the option, depending on `key_style`.
+    pub(crate) fn display(&self, key_style: KeyStyle) -> &str {
+        if key_style.prefers_label() && !self.label.is_empty() {
+            &self.label
+        } else {
+            &self.name
+        }
+    }
+
+    /// Translated value of the option, disabled options are flagged.
+    fn value(&self, key_style: KeyStyle) -> Value {
+        let display = self.display(key_style);
+
+        if self.disabled {
+            eprintln!("Picklist option {} is disabled", self.id);
+            Value::String(format!("{display} (disabled)"))
+        } else {
+            Value::String(display.to_string())
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -143,76 +374,243 @@ enum SearchQueryConditionOperator {
     Address,
 }
 
-fn parse_oa_uuid(key: &str) -> String {
+pub(crate) fn parse_oa_uuid(key: &str) -> String {
     key.trim_start_matches("oa_").replace("_", "-")
 }
 
-fn process_entity_attribute(
+/// Entity key of an attribute id, the inverse of `parse_oa_uuid`.
+pub(crate) fn oa_key(id: &str) -> String {
+    format!("oa_{}", id.replace('-', "_"))
+}
+
+/// Attributes not nested in a form, sorted by name.
+pub(crate) fn top_level_attributes(
+    hashmap: &HashMap<String, ObjectAttribute>,
+) -> Vec<&ObjectAttribute> {
+    let nested: HashSet<&String> = hashmap.values().flat_map(|oa| &oa.children).collect();
+
+    let mut attributes: Vec<&ObjectAttribute> = hashmap
+        .values()
+        .filter(|oa| !nested.contains(&oa.id))
+        .collect();
+    attributes.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+    attributes
+}
+
+/// Add the attributes of `reference` not in `hashmap` yet, positioned after
+/// the attributes already there.
+pub(crate) fn merge_reference(
+    hashmap: &mut HashMap<String, ObjectAttribute>,
+    reference: HashMap<String, ObjectAttribute>,
+) {
+    let offset = hashmap.len();
+
+    for (id, mut oa) in reference {
+        if let Entry::Vacant(entry) = hashmap.entry(id) {
+            oa.position += offset;
+            entry.insert(oa);
+        }
+    }
+}
+
+/// Nested form children of `oa` in the reference, without the forms in
+/// `ancestors`, so a form that reaches itself again isn't expanded forever.
+pub(crate) fn nested_children<'a>(
     oa: &ObjectAttribute,
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    ancestors: &[&str],
+) -> Vec<&'a ObjectAttribute> {
+    oa.children
+        .iter()
+        .filter(|id| !ancestors.contains(&id.as_str()))
+        .filter_map(|id| hashmap.get(id))
+        .collect()
+}
+
+/// Top level attributes grouped by object class, sorted by class id.
+pub(crate) fn object_classes(
+    hashmap: &HashMap<String, ObjectAttribute>,
+) -> Vec<(Option<&str>, Vec<&ObjectAttribute>)> {
+    let mut classes: BTreeMap<Option<&str>, Vec<&ObjectAttribute>> = BTreeMap::new();
+
+    for oa in top_level_attributes(hashmap) {
+        classes
+            .entry(oa.object_class_id.as_deref())
+            .or_default()
+            .push(oa);
+    }
+
+    classes.into_iter().collect()
+}
+
+/// Translated value next to the original one, when they differ.
+pub(crate) fn with_original(value: Value, original: Option<Value>) -> Value {
+    match original {
+        Some(original) if original != value => {
+            serde_json::json!({ "value": value, "original": original })
+        }
+        _ => value,
+    }
+}
+
+fn process_entity_attribute<'r>(
+    oa: &'r ObjectAttribute,
     key: String,
-    value: Value,
+    value: Cow<Value>,
     hashmap: &HashMap<String, ObjectAttribute>,
-) -> (String, Value) {
-    let name = format!("{} ({})", oa.name, oa.data_type);
+    options: &Options,
+) -> Result<(Cow<'r, str>, Value)> {
+    let name = oa.key(options.key_style);
+
+    if oa.is_redacted(options) {
+        return Ok((name, REDACTED.into()));
+    }
 
-    match oa.data_type {
-        ObjectAttributeDataType::Picklist => match value {
+    let original = (options.keep_original && oa.data_type != ObjectAttributeDataType::NestedForm)
+        .then(|| value.clone().into_owned());
+
+    // Nested forms are left to the formatters of their attributes.
+    let formatted = match oa.data_type {
+        ObjectAttributeDataType::NestedForm => None,
+        _ => options.formatters.format(oa, &value),
+    };
+
+    let value = match formatted {
+        Some(value) => value,
+        None => translate_value(oa, &key, &name, value, hashmap, options)?,
+    };
+
+    Ok((
+        name,
+        with_original(oa.apply_templates(value, options), original),
+    ))
+}
+
+/// Built-in translation of a value by the data type of its attribute, only
+/// values passed through unchanged are cloned when borrowed.
+fn translate_value(
+    oa: &ObjectAttribute,
+    key: &str,
+    name: &str,
+    value: Cow<Value>,
+    hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Value> {
+    let value = match oa.data_type {
+        ObjectAttributeDataType::Picklist => match value.as_ref() {
             Value::Null => {
-                println!(
+                eprintln!(
                     "Missing picklist value for id: {key}, which oa name is {}",
                     name
                 );
-                (name, value)
+                Value::Null
             }
-            _ => {
-                let picklist_option = oa.picklist_options.iter().find(|option| option.id == value);
+            value => {
+                let picklist_option = value.as_str().and_then(|id| oa.option(id));
 
                 match picklist_option {
-                    Some(option) => (name, serde_json::Value::String(option.name.clone())),
+                    Some(option) => option.value(options.key_style),
+                    None if options.strict => {
+                        bail!("Picklist option not found for id: {key}, value: {value}")
+                    }
                     None => {
-                        println!("Picklist option not found for id: {key}");
-                        (name, "not found".into())
+                        eprintln!("Picklist option not found for id: {key}");
+                        "not found".into()
                     }
                 }
             }
         },
-        ObjectAttributeDataType::NestedForm => match value {
-            Value::Null => (name, value),
-            _ => {
-                let nested_form_value: HashMap<String, payload::Payload> =
-                    serde_json::from_value(value).expect("failed to parse nested_form");
-
-                let values: Vec<HashMap<String, Value>> = nested_form_value
-                    .into_values()
-                    .map(|value| match value.object_entity_attribute_values {
-                        Some(values) => convert_raw_entity(values, hashmap),
-                        None => HashMap::new(),
+        ObjectAttributeDataType::NestedForm => match value.as_ref() {
+            Value::Null => Value::Null,
+            Value::Object(entries) => {
+                let values = entries
+                    .values()
+                    .map(|entry| match entry.get("object_entity_attribute_values") {
+                        Some(Value::Object(values)) => {
+                            convert_raw_entity(borrowed_attributes(values), hashmap, options)
+                        }
+                        None | Some(Value::Null) if entry.is_object() => Ok(HashMap::new()),
+                        _ => bail!("failed to parse nested_form: {entry}"),
                     })
-                    .collect();
-
-                let json = serde_json::to_value(values).expect("failed to convert values to json");
+                    .collect::<Result<Vec<HashMap<String, Value>>>>()?;
 
-                (name, json)
+                serde_json::to_value(values)?
             }
+            value => bail!("failed to parse nested_form: {value}"),
         },
-        _ => (name, value),
-    }
+        _ => format_value(&oa.data_type, value, options),
+    };
+
+    Ok(value)
 }
 
-fn convert_raw_entity(
+/// Attributes of an owned entity, moved into the translation.
+pub(crate) fn owned_attributes(
     entity: HashMap<String, Value>,
-    hashmap: &HashMap<String, ObjectAttribute>,
-) -> HashMap<String, Value> {
+) -> impl Iterator<Item = (String, Cow<'static, Value>)> {
     entity
         .into_iter()
-        .filter(|(key, _)| key.starts_with("oa_"))
-        .map(|(key, value)| (parse_oa_uuid(&key), value))
+        .map(|(key, value)| (key, Cow::Owned(value)))
+}
+
+/// Attributes of a borrowed entity, cloned only where passed through.
+pub(crate) fn borrowed_attributes(
+    entity: &Map<String, Value>,
+) -> impl Iterator<Item = (&String, Cow<'_, Value>)> {
+    entity
+        .iter()
+        .map(|(key, value)| (key, Cow::Borrowed(value)))
+}
+
+fn convert_raw_entity<'a, K: AsRef<str>>(
+    entity: impl IntoIterator<Item = (K, Cow<'a, Value>)>,
+    hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<HashMap<String, Value>> {
+    Ok(translate_entity(entity, hashmap, options)?
+        .into_iter()
+        .map(|(key, value)| (key.into_owned(), value))
+        .collect())
+}
+
+/// Translated attributes of an entity, in the order of `options.order`. Keys
+/// are borrowed from the reference where the key style allows.
+pub(crate) fn translate_entity<'r, 'a, K: AsRef<str>>(
+    entity: impl IntoIterator<Item = (K, Cow<'a, Value>)>,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Vec<(Cow<'r, str>, Value)>> {
+    let mut attributes = entity
+        .into_iter()
+        .filter(|(key, _)| key.as_ref().starts_with("oa_"))
+        .map(|(key, value)| (parse_oa_uuid(key.as_ref()), value))
         .map(|(key, value)| match hashmap.get(&key) {
-            Some(oa) => process_entity_attribute(oa, key, value, hashmap),
+            Some(oa) => Ok((
+                Some(oa),
+                process_entity_attribute(oa, key, value, hashmap, options)?,
+            )),
+            None if options.strict => bail!("Unknown object attribute id: {key}"),
             None => {
-                println!("Unknown object attribute id: {key}");
-                (key, value)
+                eprintln!("Unknown object attribute id: {key}");
+                Ok((None, (Cow::Owned(key), value.into_owned())))
             }
         })
-        .collect()
+        .collect::<Result<Vec<(Option<&ObjectAttribute>, (Cow<str>, Value))>>>()?;
+
+    // Unknown attributes are keyed by id and come last in reference order.
+    match options.order {
+        AttributeOrder::Key => attributes.sort_by(|(_, (a, _)), (_, (b, _))| a.cmp(b)),
+        AttributeOrder::Reference => attributes.sort_by_cached_key(|(oa, (key, _))| {
+            (oa.map_or(usize::MAX, |oa| oa.position), key.clone())
+        }),
+        AttributeOrder::Id => attributes.sort_by_cached_key(|(oa, (key, _))| {
+            oa.map_or_else(|| key.to_string(), |oa| oa.id.clone())
+        }),
+    }
+
+    Ok(attributes
+        .into_iter()
+        .map(|(_, attribute)| attribute)
+        .collect())
 }
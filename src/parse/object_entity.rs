@@ -1,14 +1,37 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde_json::Value;
 use std::collections::HashMap;
 
-use super::{ObjectAttribute, convert_raw_entity};
+use super::{
+    ObjectAttribute, borrowed_attributes, convert_raw_entity, owned_attributes, translate_entity,
+};
+use crate::{options::Options, translator::Entity};
 
 type ObjectEntity = HashMap<String, Value>;
 
 pub fn parse(
     entity: ObjectEntity,
     hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
 ) -> Result<ObjectEntity> {
-    Ok(convert_raw_entity(entity, hashmap))
+    convert_raw_entity(owned_attributes(entity), hashmap, options)
+}
+
+pub fn translate<'r>(
+    entity: ObjectEntity,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Entity<'r>> {
+    Ok(translate_entity(owned_attributes(entity), hashmap, options)?.into())
+}
+
+/// Translate an entity without taking or cloning it.
+pub fn translate_borrowed<'r>(
+    entity: &Value,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Entity<'r>> {
+    let attributes = entity.as_object().context("entity is not an object")?;
+
+    Ok(translate_entity(borrowed_attributes(attributes), hashmap, options)?.into())
 }
@@ -1,11 +1,18 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 use super::{
-    ObjectAttribute, ObjectAttributeDataType, SearchQuery, SearchQueryCondition,
-    SearchQueryConditionOperator, SearchQueryGroup, SearchQueryGroupOperator, convert_raw_entity,
+    ObjectAttribute, ObjectAttributeDataType, REDACTED, SearchQuery, SearchQueryCondition,
+    SearchQueryConditionOperator, SearchQueryGroup, SearchQueryGroupOperator, borrowed_attributes,
+    owned_attributes, translate_entity, with_original,
+};
+use crate::{
+    format::format_value,
+    options::Options,
+    timezone::relative_range,
+    translator::{Entity, TranslatedPayload},
 };
 
 #[derive(Deserialize)]
@@ -16,47 +23,83 @@ pub struct Payload {
 }
 
 /// Transform picklist oa id to name.
-fn process_picklist_oa_value(oa: &ObjectAttribute, value: Value) -> Value {
-    match value {
-        Value::String(option_id) => {
-            let picklist_option = oa
-                .picklist_options
-                .iter()
-                .find(|option| option.id == option_id);
-
-            match picklist_option {
-                Some(option) => serde_json::Value::String(option.name.clone()),
-                None => {
-                    println!("Picklist option not found for id: {option_id}");
-                    "not_found_picklist_label".into()
-                }
+fn process_picklist_oa_value(
+    oa: &ObjectAttribute,
+    value: Value,
+    options: &Options,
+) -> Result<Value> {
+    let translate_option = |option_id: &Value| -> Result<Value> {
+        let picklist_option = option_id.as_str().and_then(|id| oa.option(id));
+
+        let option_id = option_id
+            .as_str()
+            .map_or_else(|| option_id.to_string(), str::to_string);
+
+        match picklist_option {
+            Some(option) => Ok(option.value(options.key_style)),
+            None if options.strict => bail!("Picklist option not found for id: {option_id}"),
+            None => {
+                eprintln!("Picklist option not found for id: {option_id}");
+                Ok("not_found_picklist_label".into())
             }
         }
+    };
+
+    let value = match value {
+        Value::String(_) => translate_option(&value)?,
         Value::Array(option_ids) => option_ids
             .iter()
-            .map(|option_id| {
-                let picklist_option = oa
-                    .picklist_options
-                    .iter()
-                    .find(|option| option.id == *option_id);
-
-                match picklist_option {
-                    Some(option) => serde_json::Value::String(option.name.clone()),
-                    None => {
-                        println!("Picklist option not found for id: {option_id}");
-                        "not_found_picklist_label".into()
-                    }
-                }
-            })
-            .collect(),
+            .map(translate_option)
+            .collect::<Result<Value>>()?,
         _ => "picklist value is {option_id_value:#?}, which is not implemented yet.".into(),
+    };
+
+    Ok(value)
+}
+
+/// Built-in translation of a condition value, formatted like entity values and
+/// `between` values element wise.
+fn translate_condition_value(
+    oa: &ObjectAttribute,
+    value: Value,
+    options: &Options,
+) -> Result<Value> {
+    if oa.data_type == ObjectAttributeDataType::Picklist {
+        return process_picklist_oa_value(oa, value, options);
     }
+
+    let value = match value {
+        Value::Array(values) => values
+            .into_iter()
+            .map(|value| format_value(&oa.data_type, Cow::Owned(value), options))
+            .collect(),
+        value => format_value(&oa.data_type, Cow::Owned(value), options),
+    };
+
+    Ok(value)
+}
+
+/// Local range of a relative date operator, e.g. `today`, when a timezone is set.
+fn condition_range(oa: &ObjectAttribute, operator: &str, options: &Options) -> Option<String> {
+    let date_only = match oa.data_type {
+        ObjectAttributeDataType::Date => true,
+        ObjectAttributeDataType::Datetime => false,
+        _ => return None,
+    };
+
+    relative_range(
+        operator,
+        options.timezone.as_ref()?,
+        jiff::Timestamp::now(),
+        date_only,
+    )
 }
 
 fn parse_search_query_group(
     group: SearchQueryGroup,
     hashmap: &HashMap<String, ObjectAttribute>,
-) -> String {
+    options: &Options,
+) -> Result<String> {
     let mut lisp_expr_vec: Vec<String> = Vec::new();
 
     let operator = match group.operator {
@@ -68,36 +111,39 @@ fn parse_search_query_group(
     lisp_expr_vec.push(operator.to_string());
 
     if let Some(conditions) = group.search_query_conditions {
-        let cond_expr = parse_search_query_conditions(conditions, hashmap);
+        let cond_expr = parse_search_query_conditions(conditions, hashmap, options)?;
         lisp_expr_vec.push(cond_expr);
     }
 
     if let Some(children) = group.children {
-        let children_expr = parse_search_query_children(children, hashmap);
+        let children_expr = parse_search_query_children(children, hashmap, options)?;
         lisp_expr_vec.push(children_expr);
     }
 
     let lisp_expr = format!("({})", lisp_expr_vec.join(" "));
 
-    lisp_expr
+    Ok(lisp_expr)
 }
 
 fn parse_search_query_children(
     children: Vec<SearchQueryGroup>,
     hashmap: &HashMap<String, ObjectAttribute>,
-) -> String {
-    children
+    options: &Options,
+) -> Result<String> {
+    let exprs = children
         .into_iter()
-        .map(|child| parse_search_query_group(child, hashmap))
-        .collect::<Vec<String>>()
-        .join(" ")
+        .map(|child| parse_search_query_group(child, hashmap, options))
+        .collect::<Result<Vec<String>>>()?;
+
+    Ok(exprs.join(" "))
 }
 
 fn parse_search_query_conditions(
     conditions: Vec<SearchQueryCondition>,
     hashmap: &HashMap<String, ObjectAttribute>,
-) -> String {
-    let lisp_exprs: Vec<String> = conditions
+    options: &Options,
+) -> Result<String> {
+    let lisp_exprs = conditions
         .into_iter()
         .map(|condition| {
             let operator = match condition.operator {
@@ -136,84 +182,164 @@ fn parse_search_query_conditions(
 
             let (name, value) = match hashmap.get(&condition.object_attribute_id) {
                 Some(oa) => {
-                    let name = oa.name.clone();
+                    let mut name = oa.display_name(options.key_style);
                     let mut value = condition.value;
 
-                    if oa.data_type == ObjectAttributeDataType::Picklist {
-                        value = value.map(|value| process_picklist_oa_value(oa, value));
+                    if oa.is_redacted(options) {
+                        value = value.map(|_| REDACTED.into());
+                    } else {
+                        let original = value.clone().filter(|_| options.keep_original);
+
+                        value = value
+                            .map(|value| match options.formatters.format(oa, &value) {
+                                Some(value) => Ok(value),
+                                None => translate_condition_value(oa, value, options),
+                            })
+                            .transpose()?
+                            .map(|value| oa.apply_templates(value, options))
+                            .map(|value| with_original(value, original));
+                    }
+
+                    if let Some(range) = condition_range(oa, operator, options) {
+                        name = format!("{name} {range}").into();
                     }
 
                     (name, value)
                 }
-                None => ("not_found".to_string(), condition.value),
+                None if options.strict => bail!(
+                    "Unknown object attribute id: {}",
+                    condition.object_attribute_id
+                ),
+                None => ("not_found".into(), condition.value),
             };
 
             let args = match value {
                 Some(value) => &format!("{name} {value}"),
-                None => &name,
+                None => name.as_ref(),
             };
 
             let lisp_expr = format!("({})", [operator, args].join(" "));
 
-            lisp_expr
+            Ok(lisp_expr)
         })
-        .collect();
+        .collect::<Result<Vec<String>>>()?;
 
-    lisp_exprs.join(" ").to_string()
+    Ok(lisp_exprs.join(" "))
 }
 
 fn parse_search_query(
     search_query: SearchQuery,
     hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
 ) -> Result<String> {
-    let lisp_expr = search_query
+    let lisp_exprs = search_query
         .search_query_groups
         .into_iter()
-        .map(|group| parse_search_query_group(group, hashmap))
-        .collect::<Vec<String>>()
-        .join(" ");
+        .map(|group| parse_search_query_group(group, hashmap, options))
+        .collect::<Result<Vec<String>>>()?;
 
-    Ok(lisp_expr)
+    Ok(lisp_exprs.join(" "))
 }
 
 pub fn parse(
     payload: Payload,
     hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
 ) -> Result<HashMap<String, Value>> {
-    let mut map = HashMap::new();
-
-    if let Some(ids) = payload.object_attribute_ids {
-        let search_oa_list = ids
-            .into_iter()
-            .map(|id| {
-                let name = match hashmap.get(&id) {
-                    Some(oa) => oa.name.clone(),
-                    None => "not_found".to_string(),
-                };
-
-                format!("{name}, {id}")
-            })
-            .collect::<Vec<String>>();
-
-        map.insert("object_attributes".to_string(), search_oa_list.into());
-    }
+    Ok(translate(payload, hashmap, options)?.into())
+}
 
-    if let Some(search_query) = payload.search_query {
-        let search_query = parse_search_query(search_query, hashmap)?;
+pub fn translate<'r>(
+    payload: Payload,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<TranslatedPayload<'r>> {
+    let object_entity_attribute_values = payload
+        .object_entity_attribute_values
+        .map(|entity| translate_entity(owned_attributes(entity), hashmap, options))
+        .transpose()?;
+
+    translate_parts(
+        payload.object_attribute_ids,
+        payload.search_query,
+        object_entity_attribute_values,
+        hashmap,
+        options,
+    )
+}
 
-        map.insert(
-            "search_query".to_string(),
-            serde_json::Value::String(search_query),
-        );
-    }
+/// Translate a payload without taking it, only the attribute ids and search
+/// query are copied out.
+pub fn translate_borrowed<'r>(
+    payload: &Value,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<TranslatedPayload<'r>> {
+    let payload = payload.as_object().context("payload is not an object")?;
+
+    let object_attribute_ids = payload
+        .get("object_attribute_ids")
+        .map(Option::<Vec<String>>::deserialize)
+        .transpose()
+        .context("failed to parse object_attribute_ids")?
+        .flatten();
+
+    let search_query = payload
+        .get("search_query")
+        .map(Option::<SearchQuery>::deserialize)
+        .transpose()
+        .context("failed to parse search_query")?
+        .flatten();
+
+    let object_entity_attribute_values = match payload.get("object_entity_attribute_values") {
+        None | Some(Value::Null) => None,
+        Some(Value::Object(entity)) => Some(translate_entity(
+            borrowed_attributes(entity),
+            hashmap,
+            options,
+        )?),
+        Some(value) => bail!("object_entity_attribute_values is not an object: {value}"),
+    };
 
-    if let Some(entity) = payload.object_entity_attribute_values {
-        let object_entity = convert_raw_entity(entity, hashmap);
+    translate_parts(
+        object_attribute_ids,
+        search_query,
+        object_entity_attribute_values,
+        hashmap,
+        options,
+    )
+}
 
-        let serde_object = serde_json::to_value(object_entity)?;
+fn translate_parts<'r>(
+    object_attribute_ids: Option<Vec<String>>,
+    search_query: Option<SearchQuery>,
+    object_entity_attribute_values: Option<Vec<(Cow<'r, str>, Value)>>,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<TranslatedPayload<'r>> {
+    let object_attributes = object_attribute_ids
+        .map(|ids| {
+            ids.into_iter()
+                .map(|id| {
+                    let name = match hashmap.get(&id) {
+                        Some(oa) => oa.display_name(options.key_style),
+                        None if options.strict => bail!("Unknown object attribute id: {id}"),
+                        None => "not_found".into(),
+                    };
+
+                    Ok(format!("{name}, {id}"))
+                })
+                .collect::<Result<Vec<String>>>()
+        })
+        .transpose()?;
 
-        map.insert("object_entity_attribute_values".to_string(), serde_object);
-    }
+    let search_query = search_query
+        .map(|search_query| parse_search_query(search_query, hashmap, options))
+        .transpose()?;
 
-    Ok(map)
+    Ok(TranslatedPayload {
+        object_attributes,
+        search_query,
+        object_entity_attribute_values: object_entity_attribute_values.map(Entity::from),
+    })
 }
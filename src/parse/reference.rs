@@ -1,86 +1,181 @@
 use anyhow::Result;
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 use super::{ObjectAttribute, ObjectAttributeDataType, PicklistOption};
 
 #[derive(Deserialize)]
 pub struct ObjectAttributesRaw {
-    data: Vec<ObjectAttributesData>,
-    included: HashMap<String, ObjectAttributesIncluded>,
+    pub(crate) data: Vec<ObjectAttributesData>,
+    pub(crate) included: HashMap<String, ObjectAttributesIncluded>,
 }
 
 #[derive(Deserialize)]
-struct ObjectAttributesData {
-    id: String,
-    attributes: ObjectAttributesDataAttributes,
-    relationships: ObjectAttributesDataRelationships,
+pub(crate) struct ObjectAttributesData {
+    pub(crate) id: String,
+    pub(crate) attributes: ObjectAttributesDataAttributes,
+    pub(crate) relationships: ObjectAttributesDataRelationships,
 }
 
 #[derive(Deserialize)]
-struct ObjectAttributesDataAttributes {
-    name: String,
-    data_type: ObjectAttributeDataType,
+pub(crate) struct ObjectAttributesDataAttributes {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) label: String,
+    pub(crate) data_type: ObjectAttributeDataType,
+    #[serde(default)]
+    pub(crate) object_class_id: Option<String>,
 }
 
 #[derive(Deserialize)]
-struct ObjectAttributesDataRelationships {
-    picklist_options: Relationship<Vec<RelationshipDataPicklistOption>>,
+pub(crate) struct ObjectAttributesDataRelationships {
+    pub(crate) picklist_options: Relationship<Vec<RelationshipDataPicklistOption>>,
+    /// Attributes of a nested form.
+    #[serde(default)]
+    pub(crate) object_attributes: Relationship<Vec<RelationshipDataObjectAttribute>>,
 }
 
 #[derive(Deserialize)]
-struct ObjectAttributesIncluded {
-    id: String,
-    attributes: ObjectAttributesIncludedAttributes,
+pub(crate) struct ObjectAttributesIncluded {
+    pub(crate) id: String,
+    pub(crate) attributes: ObjectAttributesIncludedAttributes,
 }
 
 #[derive(Deserialize)]
-struct ObjectAttributesIncludedAttributes {
-    name: String,
+pub(crate) struct ObjectAttributesIncludedAttributes {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) label: String,
+    #[serde(default)]
+    pub(crate) disabled: bool,
+    #[serde(default, alias = "order")]
+    pub(crate) position: Option<i64>,
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
+}
+
+#[derive(Deserialize, Default)]
+pub(crate) struct Relationship<T> {
+    pub(crate) data: Option<T>,
 }
 
 #[derive(Deserialize)]
-struct Relationship<T> {
-    data: Option<T>,
+pub(crate) struct RelationshipDataPicklistOption {
+    pub(crate) id: String,
 }
 
 #[derive(Deserialize)]
-struct RelationshipDataPicklistOption {
-    id: String,
+pub(crate) struct RelationshipDataObjectAttribute {
+    pub(crate) id: String,
+}
+
+impl From<ObjectAttributesIncluded> for PicklistOption {
+    fn from(included: ObjectAttributesIncluded) -> Self {
+        PicklistOption {
+            id: included.id,
+            name: included.attributes.name,
+            label: included.attributes.label,
+            disabled: included.attributes.disabled,
+            position: included.attributes.position,
+            extra: included.attributes.extra,
+        }
+    }
 }
 
 pub fn parse(raw_data: ObjectAttributesRaw) -> Result<HashMap<String, ObjectAttribute>> {
     let mut map = HashMap::with_capacity(raw_data.data.len());
+    let mut unknown_data_types: Vec<String> = Vec::new();
+
+    // Options are moved out on their last use, shared ones are cloned before that.
+    let mut included: HashMap<String, (usize, PicklistOption)> = raw_data
+        .included
+        .into_iter()
+        .map(|(id, included)| (id, (0, included.into())))
+        .collect();
 
     for oa in &raw_data.data {
-        let picklist_options = oa
+        for option in oa.relationships.picklist_options.data.iter().flatten() {
+            if let Some((count, _)) = included.get_mut(&option.id) {
+                *count += 1;
+            }
+        }
+    }
+
+    for oa in raw_data.data {
+        if map.contains_key(&oa.id) {
+            continue;
+        }
+
+        if let ObjectAttributeDataType::Unknown(data_type) = &oa.attributes.data_type
+            && !unknown_data_types.contains(data_type)
+        {
+            unknown_data_types.push(data_type.clone());
+        }
+
+        let mut picklist_options: Vec<PicklistOption> = oa
             .relationships
             .picklist_options
             .data
-            .as_deref()
             .unwrap_or_default()
-            .iter()
-            .map(|option| {
-                raw_data
-                    .included
-                    .get(&option.id)
-                    .map(|included| PicklistOption {
-                        id: included.id.clone(),
-                        name: included.attributes.name.clone(),
-                    })
-                    .unwrap_or_else(|| PicklistOption {
-                        id: option.id.to_string(),
+            .into_iter()
+            .map(
+                |option| match included.get(&option.id).map(|(count, _)| *count) {
+                    Some(1) => included
+                        .remove(&option.id)
+                        .map(|(_, picklist_option)| picklist_option)
+                        .expect("included option exists"),
+                    Some(_) => {
+                        let (count, picklist_option) = included
+                            .get_mut(&option.id)
+                            .expect("included option exists");
+                        *count -= 1;
+                        picklist_option.clone()
+                    }
+                    None => PicklistOption {
+                        id: option.id,
                         name: "not_found".to_string(),
-                    })
-            })
+                        label: String::new(),
+                        disabled: false,
+                        position: None,
+                        extra: Map::new(),
+                    },
+                },
+            )
             .collect();
 
-        map.entry(oa.id.to_string())
-            .or_insert_with(|| ObjectAttribute {
-                data_type: oa.attributes.data_type,
-                name: oa.attributes.name.clone(),
-                picklist_options,
-            });
+        let children = oa
+            .relationships
+            .object_attributes
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| child.id)
+            .collect();
+
+        // Options without position keep their relationship order, after positioned ones.
+        picklist_options.sort_by_key(|option| (option.position.is_none(), option.position));
+
+        let mut object_attribute = ObjectAttribute::new(
+            oa.id.clone(),
+            oa.attributes.object_class_id,
+            oa.attributes.data_type,
+            oa.attributes.name,
+            oa.attributes.label,
+            picklist_options,
+            children,
+        );
+        object_attribute.position = map.len();
+
+        map.insert(oa.id, object_attribute);
+    }
+
+    if !unknown_data_types.is_empty() {
+        unknown_data_types.sort_unstable();
+        eprintln!(
+            "Unrecognized data types, values are passed through: {}",
+            unknown_data_types.join(", ")
+        );
     }
 
     Ok(map)
@@ -1,10 +1,21 @@
-use anyhow::Result;
-use std::collections::HashMap;
+use anyhow::{Context, Result};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufReader, Read},
+};
 
-use serde::Deserialize;
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+};
 use serde_json::Value;
 
-use super::{ObjectAttribute, ObjectEntity, convert_raw_entity};
+use super::{
+    ObjectAttribute, ObjectEntity, borrowed_attributes, convert_raw_entity, owned_attributes,
+    translate_entity,
+};
+use crate::{options::Options, translator::Entity};
 
 #[derive(Deserialize)]
 pub struct Response {
@@ -14,12 +25,165 @@ pub struct Response {
 pub fn parse(
     response: Response,
     hashmap: &HashMap<String, ObjectAttribute>,
+    options: &Options,
 ) -> Result<Vec<HashMap<String, Value>>> {
-    let result = response
+    response
         .data
+        .into_iter()
+        .map(|entity| convert_raw_entity(owned_attributes(entity.attributes), hashmap, options))
+        .collect()
+}
+
+pub fn translate<'r>(
+    response: Response,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Vec<Entity<'r>>> {
+    response
+        .data
+        .into_iter()
+        .map(|entity| {
+            translate_entity(owned_attributes(entity.attributes), hashmap, options)
+                .map(Entity::from)
+        })
+        .collect()
+}
+
+/// Translate a response without taking or cloning it.
+pub fn translate_borrowed<'r>(
+    response: &Value,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &Options,
+) -> Result<Vec<Entity<'r>>> {
+    response
+        .get("data")
+        .and_then(Value::as_array)
+        .context("response has no data array")?
         .iter()
-        .map(|entity| convert_raw_entity(entity.attributes.clone(), hashmap))
-        .collect();
+        .map(|entity| {
+            let attributes = entity
+                .get("attributes")
+                .and_then(Value::as_object)
+                .context("entity of the response has no attributes object")?;
+
+            translate_entity(borrowed_attributes(attributes), hashmap, options).map(Entity::from)
+        })
+        .collect()
+}
+
+/// Translates the entities of a response one at a time while it is read.
+struct StreamVisitor<'a, F> {
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    options: &'a Options,
+    each: F,
+    count: usize,
+    /// Error of a translation, serde errors can only carry its message.
+    error: Option<anyhow::Error>,
+}
+
+impl<'a, F: FnMut(Entity<'a>) -> Result<()>> StreamVisitor<'a, F> {
+    fn entity<E: de::Error>(&mut self, entity: ObjectEntity) -> Result<(), E> {
+        let result = translate_entity(
+            owned_attributes(entity.attributes),
+            self.hashmap,
+            self.options,
+        )
+        .map(Entity::from)
+        .and_then(|entity| (self.each)(entity));
+
+        match result {
+            Ok(()) => {
+                self.count += 1;
+                Ok(())
+            }
+            Err(error) => {
+                let message = error.to_string();
+                self.error = Some(error);
+                Err(E::custom(message))
+            }
+        }
+    }
+}
+
+impl<'de, 'a, F: FnMut(Entity<'a>) -> Result<()>> Visitor<'de> for &mut StreamVisitor<'a, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a response object with a data array")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut data = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                map.next_value_seed(DataSeed(&mut *self))?;
+                data = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        if !data {
+            return Err(de::Error::missing_field("data"));
+        }
+
+        Ok(())
+    }
+}
+
+/// The `data` array of a response.
+struct DataSeed<'v, 'a, F>(&'v mut StreamVisitor<'a, F>);
+
+impl<'de, 'a, F: FnMut(Entity<'a>) -> Result<()>> DeserializeSeed<'de> for DataSeed<'_, 'a, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, F: FnMut(Entity<'a>) -> Result<()>> Visitor<'de> for DataSeed<'_, 'a, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of entities")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(entity) = seq.next_element::<ObjectEntity>()? {
+            self.0.entity(entity)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Translate a response while reading it, passing each entity to `each` as
+/// soon as it is translated. Only one entity is held in memory at a time,
+/// returns the number of entities.
+pub fn translate_stream<'r>(
+    reader: impl Read,
+    hashmap: &'r HashMap<String, ObjectAttribute>,
+    options: &'r Options,
+    each: impl FnMut(Entity<'r>) -> Result<()>,
+) -> Result<usize> {
+    let mut visitor = StreamVisitor {
+        hashmap,
+        options,
+        each,
+        count: 0,
+        error: None,
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+
+    let result = deserializer
+        .deserialize_map(&mut visitor)
+        .and_then(|()| deserializer.end());
 
-    Ok(result)
+    match (result, visitor.error) {
+        (Err(_), Some(error)) => Err(error),
+        (Err(error), None) => Err(error).context("failed to read response"),
+        (Ok(()), _) => Ok(visitor.count),
+    }
 }
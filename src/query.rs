@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+use serde_json::{Value, json};
+
+use crate::{
+    ObjectAttribute,
+    parse::{ObjectAttributeDataType, parse_oa_uuid},
+};
+
+/// Condition operators without a value.
+const VALUELESS_OPERATORS: [&str; 20] = [
+    "is_present",
+    "is_blank",
+    "today",
+    "before_today",
+    "after_today",
+    "this_week",
+    "before_this_week",
+    "after_this_week",
+    "this_month",
+    "before_this_month",
+    "after_this_month",
+    "this_quarter",
+    "before_this_quarter",
+    "after_this_quarter",
+    "this_year",
+    "before_this_year",
+    "after_this_year",
+    "is_true",
+    "is_false",
+    "address",
+];
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    /// Word, quoted string or json value, kept as written.
+    Atom(String),
+}
+
+/// Tokens of a query, strings, arrays and objects are single tokens.
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut atom = String::new();
+                let mut depth = 0;
+                let mut in_string = false;
+                let mut escaped = false;
+
+                while let Some(&c) = chars.peek() {
+                    if !in_string && depth == 0 && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' if in_string => escaped = true,
+                        '"' => in_string = !in_string,
+                        '[' | '{' if !in_string => depth += 1,
+                        ']' | '}' if !in_string => depth -= 1,
+                        _ => {}
+                    }
+
+                    atom.push(c);
+                    chars.next();
+                }
+
+                if in_string || depth != 0 {
+                    bail!("unterminated string or array: {atom}");
+                }
+
+                tokens.push(Token::Atom(atom));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Group or condition of a query, before names are resolved.
+#[derive(Debug)]
+enum Expr {
+    List(Vec<Expr>),
+    Atom(String),
+}
+
+fn parse_expr(tokens: &[Token], position: &mut usize) -> Result<Expr> {
+    match tokens.get(*position) {
+        Some(Token::Open) => {
+            *position += 1;
+            let mut items = Vec::new();
+
+            loop {
+                match tokens.get(*position) {
+                    Some(Token::Close) => {
+                        *position += 1;
+                        return Ok(Expr::List(items));
+                    }
+                    Some(_) => items.push(parse_expr(tokens, position)?),
+                    None => bail!("missing closing parenthesis"),
+                }
+            }
+        }
+        Some(Token::Atom(atom)) => {
+            *position += 1;
+            Ok(Expr::Atom(atom.clone()))
+        }
+        Some(Token::Close) => bail!("unexpected closing parenthesis"),
+        None => bail!("empty query"),
+    }
+}
+
+/// Payload operator of a condition operator, as printed or as in a payload.
+fn condition_operator(operator: &str) -> &str {
+    match operator {
+        "greater_than" => "greater",
+        "greater_than_equal" => "greater_or_equal",
+        "less_than" => "less",
+        "less_than_equal" => "less_or_equal",
+        operator => operator,
+    }
+}
+
+/// Attributes whose id, name or label is `name`.
+fn find_attributes<'a>(
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    name: &str,
+) -> Vec<&'a ObjectAttribute> {
+    if let Some(oa) = hashmap.get(&parse_oa_uuid(name)) {
+        return vec![oa];
+    }
+
+    let mut found: Vec<&ObjectAttribute> = hashmap
+        .values()
+        .filter(|oa| oa.name == name || oa.label == name)
+        .collect();
+
+    if found.is_empty() {
+        found = hashmap
+            .values()
+            .filter(|oa| oa.name.eq_ignore_ascii_case(name) || oa.label.eq_ignore_ascii_case(name))
+            .collect();
+    }
+
+    found.sort_by(|a, b| a.id.cmp(&b.id));
+    found
+}
+
+/// Attribute of a name as printed in a query: its id, name or label,
+/// optionally followed by its data type or id in parentheses or brackets.
+fn resolve_attribute<'a>(
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    name: &str,
+) -> Result<&'a ObjectAttribute> {
+    let mut found = find_attributes(hashmap, name);
+
+    // Names of the label-id key style end with `[id]`, keys with `(type)`.
+    let suffixed = name
+        .strip_suffix(')')
+        .and_then(|name| name.rsplit_once(" ("))
+        .or_else(|| {
+            name.strip_suffix(']')
+                .and_then(|name| name.rsplit_once(" ["))
+        });
+
+    if found.is_empty()
+        && let Some((base, suffix)) = suffixed
+    {
+        found = find_attributes(hashmap, suffix);
+
+        if found.is_empty() {
+            found = find_attributes(hashmap, base)
+                .into_iter()
+                .filter(|oa| suffix == oa.data_type.to_string() || suffix == oa.label)
+                .collect();
+        }
+
+        if found.is_empty() {
+            found = find_attributes(hashmap, base);
+        }
+    }
+
+    match found.as_slice() {
+        [oa] => Ok(oa),
+        [] => bail!("unknown attribute: {name}"),
+        found => bail!(
+            "ambiguous attribute: {name}, use one of the ids {}",
+            found
+                .iter()
+                .map(|oa| oa.id.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Value of a condition, picklist options by id, name or label. Values
+/// printed with their original value are given the original.
+fn condition_value(oa: &ObjectAttribute, atom: &str) -> Result<Value> {
+    let value = match serde_json::from_str(atom) {
+        Ok(Value::Object(mut value)) if value.contains_key("original") => value["original"].take(),
+        Ok(value) => value,
+        Err(_) => Value::String(atom.to_string()),
+    };
+
+    if oa.data_type != ObjectAttributeDataType::Picklist {
+        return Ok(value);
+    }
+
+    let option_id = |value: &Value| -> Result<Value> {
+        let Some(option) = value.as_str() else {
+            bail!("picklist option should be a string: {value}");
+        };
+        let option = option.strip_suffix(" (disabled)").unwrap_or(option);
+
+        match oa.option(option).or_else(|| oa.option_by_label(option)) {
+            Some(option) => Ok(option.id.clone().into()),
+            None => bail!("unknown picklist option of {}: {option}", oa.name),
+        }
+    };
+
+    match value {
+        Value::Array(values) => values.iter().map(option_id).collect(),
+        value => option_id(&value),
+    }
+}
+
+/// Expression as written, lists with their parentheses.
+fn text(expr: &Expr) -> String {
+    match expr {
+        Expr::Atom(atom) => atom.clone(),
+        Expr::List(items) => format!(
+            "({})",
+            items.iter().map(text).collect::<Vec<String>>().join(" ")
+        ),
+    }
+}
+
+fn condition(hashmap: &HashMap<String, ObjectAttribute>, items: &[Expr]) -> Result<Value> {
+    // Lists within a condition are part of a name, e.g. `Amount (EUR)`.
+    let atoms = items
+        .iter()
+        .map(|item| match item {
+            Expr::List(parts) if is_group(parts) => bail!("conditions can't contain groups"),
+            item => Ok(text(item)),
+        })
+        .collect::<Result<Vec<String>>>()?;
+    let atoms: Vec<&str> = atoms.iter().map(String::as_str).collect();
+
+    let Some((operator, rest)) = atoms.split_first() else {
+        bail!("empty condition");
+    };
+    let operator = condition_operator(operator);
+
+    let (name, value) = match rest {
+        [] => bail!("condition {operator} has no attribute"),
+        rest if VALUELESS_OPERATORS.contains(&operator) => (rest, None),
+        [_] => bail!("condition {operator} has no value"),
+        [.., value] if matches!(items.last(), Some(Expr::List(_))) => {
+            bail!("condition {operator} has no value, {value} is part of its attribute")
+        }
+        [name @ .., value] => (name, Some(*value)),
+    };
+
+    let join = |atoms: &[&str]| {
+        atoms
+            .iter()
+            .map(|atom| atom.trim_matches('"'))
+            .collect::<Vec<&str>>()
+            .join(" ")
+    };
+
+    // Relative date conditions may be printed with their range, e.g.
+    // `(today Due 2025-08-08..2025-08-09)`.
+    let oa = match (resolve_attribute(hashmap, &join(name)), name) {
+        (Err(_), [name @ .., range]) if !name.is_empty() && range.contains("..") => {
+            resolve_attribute(hashmap, &join(name))?
+        }
+        (oa, _) => oa?,
+    };
+
+    let mut condition = json!({
+        "object_attribute_id": oa.id,
+        "operator": operator,
+    });
+
+    if let Some(value) = value {
+        condition["value"] = condition_value(oa, value)?;
+    }
+
+    Ok(condition)
+}
+
+fn group(hashmap: &HashMap<String, ObjectAttribute>, expr: &Expr) -> Result<Value> {
+    let Expr::List(items) = expr else {
+        bail!("expected a group such as (AND ...), found {expr:?}");
+    };
+
+    let operator = match items.first() {
+        Some(Expr::Atom(operator)) => operator.to_uppercase(),
+        _ => bail!("group should start with AND, OR or NOT"),
+    };
+
+    if !["AND", "OR", "NOT"].contains(&operator.as_str()) {
+        bail!("unknown group operator: {operator}, expected AND, OR or NOT");
+    }
+
+    let mut conditions = Vec::new();
+    let mut children = Vec::new();
+
+    for item in &items[1..] {
+        match item {
+            Expr::List(parts) if is_group(parts) => children.push(group(hashmap, item)?),
+            Expr::List(parts) => conditions.push(condition(hashmap, parts)?),
+            Expr::Atom(atom) => bail!("unexpected {atom} in group {operator}"),
+        }
+    }
+
+    let mut group = json!({ "operator": operator });
+
+    if !conditions.is_empty() {
+        group["search_query_conditions"] = conditions.into();
+    }
+
+    if !children.is_empty() {
+        group["children"] = children.into();
+    }
+
+    Ok(group)
+}
+
+fn is_group(parts: &[Expr]) -> bool {
+    matches!(parts.first(), Some(Expr::Atom(operator))
+        if ["AND", "OR", "NOT"].contains(&operator.to_uppercase().as_str()))
+}
+
+/// Payload of a search query written like a translated one, e.g.
+/// `(AND (equal Status "Open") (OR (is_present Due date)))`. Attributes are
+/// given by id, name or label, which may contain parentheses like
+/// `Amount (EUR)`, picklist options by id, name or label.
+pub fn parse(hashmap: &HashMap<String, ObjectAttribute>, text: &str) -> Result<Value> {
+    let tokens = tokenize(text)?;
+    let mut position = 0;
+    let mut groups = Vec::new();
+
+    while position < tokens.len() {
+        groups.push(group(hashmap, &parse_expr(&tokens, &mut position)?)?);
+    }
+
+    if groups.is_empty() {
+        bail!("empty query");
+    }
+
+    Ok(json!({ "search_query": { "search_query_groups": groups } }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference with a picklist, a currency with parentheses in its name and
+    /// a date.
+    fn reference() -> HashMap<String, ObjectAttribute> {
+        let attribute = |id: &str, data_type: &str, name: &str, options: Value| {
+            json!({
+                "type": "ObjectAttribute",
+                "id": id,
+                "attributes": { "data_type": data_type, "label": name, "name": name },
+                "relationships": { "picklist_options": { "data": options } }
+            })
+        };
+        let option = |id: &str, name: &str| {
+            json!({
+                "type": "PicklistOption",
+                "id": id,
+                "attributes": { "disabled": false, "label": name, "name": name }
+            })
+        };
+
+        let raw = json!({
+            "data": [
+                attribute(
+                    "status",
+                    "picklist",
+                    "Status",
+                    json!([{ "id": "open", "type": "PicklistOption" }])
+                ),
+                attribute("amount", "currency", "Amount (EUR)", Value::Null),
+                attribute("due", "date", "Due", Value::Null),
+            ],
+            "included": { "open": option("open", "Open") }
+        });
+
+        crate::preprocess_reference(serde_json::from_value(raw).unwrap()).unwrap()
+    }
+
+    fn error(query: &str) -> String {
+        parse(&reference(), query).unwrap_err().to_string()
+    }
+
+    fn conditions(query: &str) -> Value {
+        parse(&reference(), query).unwrap()["search_query"]["search_query_groups"][0]
+            ["search_query_conditions"]
+            .take()
+    }
+
+    #[test]
+    fn tokenizes_quoted_strings() {
+        assert_eq!(
+            tokenize(r#"(equal Note "a \" (b)")"#).unwrap(),
+            [
+                Token::Open,
+                Token::Atom("equal".to_string()),
+                Token::Atom("Note".to_string()),
+                Token::Atom(r#""a \" (b)""#.to_string()),
+                Token::Close,
+            ]
+        );
+        assert_eq!(
+            tokenize(r#"(in Status ["a b", "c"])"#).unwrap()[3],
+            Token::Atom(r#"["a b", "c"]"#.to_string())
+        );
+        assert!(tokenize(r#"(equal Note "a \")"#).is_err());
+        assert!(tokenize("(in Status [1, 2)").is_err());
+    }
+
+    #[test]
+    fn rejects_unbalanced_parentheses() {
+        assert_eq!(
+            error("(AND (equal Status Open)"),
+            "missing closing parenthesis"
+        );
+        assert_eq!(
+            error("(AND (equal Status Open)))"),
+            "unexpected closing parenthesis"
+        );
+        assert_eq!(error("  "), "empty query");
+    }
+
+    #[test]
+    fn rejects_unknown_operators_and_names() {
+        assert_eq!(
+            error("(XOR (equal Status Open))"),
+            "unknown group operator: XOR, expected AND, OR or NOT"
+        );
+        assert_eq!(
+            error("(AND (equal Priority 1))"),
+            "unknown attribute: Priority"
+        );
+        assert_eq!(
+            error("(AND (equal Status Closed))"),
+            "unknown picklist option of Status: Closed"
+        );
+        assert_eq!(error("(AND Status)"), "unexpected Status in group AND");
+    }
+
+    #[test]
+    fn resolves_names_with_parentheses() {
+        assert_eq!(
+            conditions("(AND (greater_than Amount (EUR) 10))"),
+            json!([{ "object_attribute_id": "amount", "operator": "greater", "value": 10 }])
+        );
+        assert_eq!(
+            conditions(r#"(AND (equal Status "Open") (is_present Amount (EUR)))"#),
+            json!([
+                { "object_attribute_id": "status", "operator": "equal", "value": "open" },
+                { "object_attribute_id": "amount", "operator": "is_present" }
+            ])
+        );
+        assert_eq!(
+            error("(AND (equal Amount (EUR)))"),
+            "condition equal has no value, (EUR) is part of its attribute"
+        );
+        assert_eq!(
+            conditions("(AND (today Due 2025-08-08..2025-08-09))"),
+            json!([{ "object_attribute_id": "due", "operator": "today" }])
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::{Error, Result, bail};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    ObjectAttribute,
+    dictionary::escape_html,
+    parse::{PicklistOption, nested_children, top_level_attributes},
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum SchemaFormat {
+    #[default]
+    Table,
+    Markdown,
+    Json,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(SchemaFormat::Table),
+            "markdown" => Ok(SchemaFormat::Markdown),
+            "json" => Ok(SchemaFormat::Json),
+            _ => bail!("unknown schema format: {s}, expected one of table, markdown, json"),
+        }
+    }
+}
+
+impl Display for SchemaFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaFormat::Table => write!(f, "table"),
+            SchemaFormat::Markdown => write!(f, "markdown"),
+            SchemaFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Attributes to list, an empty filter lists everything.
+#[derive(Clone, Default)]
+pub struct SchemaFilter {
+    /// Exact data type, e.g. `picklist`.
+    pub data_type: Option<String>,
+    /// Case insensitive substring of the name or label.
+    pub name: Option<String>,
+}
+
+impl SchemaFilter {
+    fn matches(&self, oa: &ObjectAttribute) -> bool {
+        let data_type_matches = self
+            .data_type
+            .as_ref()
+            .is_none_or(|data_type| oa.data_type.to_string() == *data_type);
+
+        let name_matches = self.name.as_ref().is_none_or(|name| {
+            let name = name.to_lowercase();
+            oa.name.to_lowercase().contains(&name) || oa.label.to_lowercase().contains(&name)
+        });
+
+        data_type_matches && name_matches
+    }
+}
+
+#[derive(Serialize)]
+struct SchemaAttribute<'a> {
+    id: &'a str,
+    name: &'a str,
+    label: &'a str,
+    data_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    picklist_options: Vec<SchemaOption<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<SchemaAttribute<'a>>,
+}
+
+#[derive(Serialize)]
+struct SchemaOption<'a> {
+    id: &'a str,
+    name: &'a str,
+    label: &'a str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    disabled: bool,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    extra: &'a Map<String, Value>,
+}
+
+impl SchemaOption<'_> {
+    fn notes(&self) -> String {
+        let mut notes = Vec::new();
+
+        if self.disabled {
+            notes.push("[disabled]".to_string());
+        }
+
+        if !self.extra.is_empty() {
+            notes.push(Value::Object(self.extra.clone()).to_string());
+        }
+
+        notes.join(" ")
+    }
+}
+
+impl<'a> From<&'a PicklistOption> for SchemaOption<'a> {
+    fn from(option: &'a PicklistOption) -> Self {
+        SchemaOption {
+            id: &option.id,
+            name: &option.name,
+            label: &option.label,
+            disabled: option.disabled,
+            extra: &option.extra,
+        }
+    }
+}
+
+/// Attribute with its nested form children, `None` when neither matches the filter.
+/// Forms in `ancestors` aren't listed again below themselves.
+fn schema_attribute<'a>(
+    oa: &'a ObjectAttribute,
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    filter: &SchemaFilter,
+    ancestors: &mut Vec<&'a str>,
+) -> Option<SchemaAttribute<'a>> {
+    ancestors.push(&oa.id);
+    let children: Vec<SchemaAttribute> = nested_children(oa, hashmap, ancestors)
+        .into_iter()
+        .filter_map(|child| schema_attribute(child, hashmap, filter, ancestors))
+        .collect();
+    ancestors.pop();
+
+    if children.is_empty() && !filter.matches(oa) {
+        return None;
+    }
+
+    Some(SchemaAttribute {
+        id: &oa.id,
+        name: &oa.name,
+        label: oa.label(),
+        data_type: oa.data_type.to_string(),
+        picklist_options: oa.picklist_options.iter().map(SchemaOption::from).collect(),
+        children,
+    })
+}
+
+/// Top level attributes sorted by name, nested form children below their form.
+fn schema_attributes<'a>(
+    hashmap: &'a HashMap<String, ObjectAttribute>,
+    filter: &SchemaFilter,
+) -> Vec<SchemaAttribute<'a>> {
+    top_level_attributes(hashmap)
+        .into_iter()
+        .filter_map(|oa| schema_attribute(oa, hashmap, filter, &mut Vec::new()))
+        .collect()
+}
+
+/// Rows of id, name, label, data type and notes, indented by depth.
+fn table_rows(attributes: &[SchemaAttribute], depth: usize, rows: &mut Vec<[String; 5]>) {
+    let indent = "  ".repeat(depth);
+
+    for oa in attributes {
+        rows.push([
+            format!("{indent}{}", oa.id),
+            oa.name.to_string(),
+            oa.label.to_string(),
+            oa.data_type.clone(),
+            String::new(),
+        ]);
+
+        for option in &oa.picklist_options {
+            rows.push([
+                format!("{indent}  {}", option.id),
+                option.name.to_string(),
+                option.label.to_string(),
+                "option".to_string(),
+                option.notes(),
+            ]);
+        }
+
+        table_rows(&oa.children, depth + 1, rows);
+    }
+}
+
+fn render_table(attributes: &[SchemaAttribute]) -> String {
+    let mut rows = vec![["ID", "NAME", "LABEL", "TYPE", "NOTES"].map(str::to_string)];
+    table_rows(attributes, 0, &mut rows);
+
+    let widths: Vec<usize> = (0..5)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Escape `|` and line breaks so cells stay in their markdown table column.
+pub(crate) fn markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown_rows(attributes: &[SchemaAttribute], parent: Option<&str>, rows: &mut Vec<String>) {
+    for oa in attributes {
+        let name = match parent {
+            Some(parent) => format!("{parent} / {}", oa.name),
+            None => oa.name.to_string(),
+        };
+
+        let options = oa
+            .picklist_options
+            .iter()
+            .map(|option| {
+                let notes = option.notes();
+                let option = format!("{} (`{}`)", escape_html(option.name), option.id);
+
+                if notes.is_empty() {
+                    option
+                } else {
+                    format!("{option} {notes}")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("<br>");
+
+        rows.push(format!(
+            "| `{}` | {} | {} | {} | {} |",
+            oa.id,
+            markdown_cell(&escape_html(&name)),
+            markdown_cell(&escape_html(oa.label)),
+            oa.data_type,
+            markdown_cell(&options)
+        ));
+
+        markdown_rows(&oa.children, Some(&name), rows);
+    }
+}
+
+/// Markdown may contain html, so text is escaped like in the dictionary.
+fn render_markdown(attributes: &[SchemaAttribute]) -> String {
+    let mut rows = vec![
+        "| ID | Name | Label | Data type | Options |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    markdown_rows(attributes, None, &mut rows);
+
+    rows.join("\n")
+}
+
+pub fn render(
+    oa_id_hashmap: &HashMap<String, ObjectAttribute>,
+    filter: &SchemaFilter,
+    format: SchemaFormat,
+) -> Result<String> {
+    let attributes = schema_attributes(oa_id_hashmap, filter);
+
+    match format {
+        SchemaFormat::Table => Ok(render_table(&attributes)),
+        SchemaFormat::Markdown => Ok(render_markdown(&attributes)),
+        SchemaFormat::Json => Ok(serde_json::to_string_pretty(&attributes)?),
+    }
+}
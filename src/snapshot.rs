@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+
+use crate::ObjectAttribute;
+
+const MAGIC: &str = "json-replacer-snapshot";
+const VERSION: u32 = 2;
+
+/// FNV-1a, enough to detect truncated or edited snapshots.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn is_snapshot(content: &str) -> bool {
+    content.starts_with(MAGIC)
+}
+
+/// Serialize parsed attributes, the header line holds format version and body checksum.
+pub fn build(oa_id_hashmap: &HashMap<String, ObjectAttribute>) -> Result<String> {
+    let mut attributes: Vec<&ObjectAttribute> = oa_id_hashmap.values().collect();
+    attributes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let body = serde_json::to_string(&attributes)?;
+
+    Ok(format!(
+        "{MAGIC} {VERSION} {:016x}\n{body}",
+        checksum(body.as_bytes())
+    ))
+}
+
+pub fn load(snapshot: &str) -> Result<HashMap<String, ObjectAttribute>> {
+    let (header, body) = snapshot
+        .split_once('\n')
+        .context("snapshot is missing its header")?;
+
+    let mut fields = header.split(' ');
+
+    if fields.next() != Some(MAGIC) {
+        bail!("not a json-replacer snapshot");
+    }
+
+    let version: u32 = fields
+        .next()
+        .and_then(|version| version.parse().ok())
+        .context("snapshot version is missing")?;
+
+    if version != VERSION {
+        bail!("unsupported snapshot version {version}, expected {VERSION}");
+    }
+
+    let expected = fields.next().context("snapshot checksum is missing")?;
+    let actual = format!("{:016x}", checksum(body.as_bytes()));
+
+    if expected != actual {
+        bail!("snapshot checksum mismatch, expected {expected}, got {actual}");
+    }
+
+    let attributes: Vec<ObjectAttribute> = serde_json::from_str(body)?;
+
+    Ok(attributes
+        .into_iter()
+        .map(|mut oa| {
+            oa.index_options();
+            (oa.id.clone(), oa)
+        })
+        .collect())
+}
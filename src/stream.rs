@@ -0,0 +1,84 @@
+use std::{fmt::Display, io::Write, str::FromStr};
+
+use anyhow::{Error, Result, bail};
+
+use crate::translator::Entity;
+
+/// Layout of streamed entities.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum StreamFormat {
+    /// One compact json object per line.
+    #[default]
+    Ndjson,
+    /// A json array, one entity per line.
+    Array,
+}
+
+impl FromStr for StreamFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ndjson" => Ok(StreamFormat::Ndjson),
+            "array" => Ok(StreamFormat::Array),
+            _ => bail!("unknown stream format: {s}, expected one of ndjson, array"),
+        }
+    }
+}
+
+impl Display for StreamFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamFormat::Ndjson => write!(f, "ndjson"),
+            StreamFormat::Array => write!(f, "array"),
+        }
+    }
+}
+
+/// Writes translated entities as they come, without holding on to them.
+pub struct EntityWriter<W: Write> {
+    writer: W,
+    format: StreamFormat,
+    count: usize,
+}
+
+impl<W: Write> EntityWriter<W> {
+    pub fn new(writer: W, format: StreamFormat) -> Self {
+        EntityWriter {
+            writer,
+            format,
+            count: 0,
+        }
+    }
+
+    pub fn write(&mut self, entity: &Entity) -> Result<()> {
+        match (self.format, self.count) {
+            (StreamFormat::Ndjson, _) => {}
+            (StreamFormat::Array, 0) => self.writer.write_all(b"[\n")?,
+            (StreamFormat::Array, _) => self.writer.write_all(b",\n")?,
+        }
+
+        serde_json::to_writer(&mut self.writer, entity)?;
+
+        if self.format == StreamFormat::Ndjson {
+            self.writer.write_all(b"\n")?;
+        }
+
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Close the array and flush, returns the number of entities written.
+    pub fn finish(mut self) -> Result<usize> {
+        match (self.format, self.count) {
+            (StreamFormat::Ndjson, _) => {}
+            (StreamFormat::Array, 0) => self.writer.write_all(b"[]\n")?,
+            (StreamFormat::Array, _) => self.writer.write_all(b"\n]\n")?,
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.count)
+    }
+}
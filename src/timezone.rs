@@ -0,0 +1,220 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{Context, Error, bail};
+use jiff::{
+    Timestamp, ToSpan, Zoned,
+    civil::{self, Date},
+    tz::{Offset, TimeZone},
+};
+use serde::{Deserialize, Serialize};
+
+/// Timezone datetimes are shown in, an IANA name such as `Europe/Berlin`
+/// or a fixed offset such as `+08:00`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timezone {
+    name: String,
+    time_zone: TimeZone,
+}
+
+impl Timezone {
+    pub fn utc() -> Self {
+        Timezone {
+            name: "UTC".to_string(),
+            time_zone: TimeZone::UTC,
+        }
+    }
+
+    pub(crate) fn to_zoned(&self, timestamp: Timestamp) -> Zoned {
+        timestamp.to_zoned(self.time_zone.clone())
+    }
+
+    /// Start of a day in this timezone.
+    fn start_of(&self, date: Date) -> Option<Zoned> {
+        date.to_zoned(self.time_zone.clone()).ok()
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Timezone::utc()
+    }
+}
+
+/// Offset of `+08`, `-0530`, `+05:30`, `UTC+2` or `GMT-03:00`.
+fn parse_offset(s: &str) -> Option<Offset> {
+    let s = s
+        .strip_prefix("UTC")
+        .or_else(|| s.strip_prefix("GMT"))
+        .unwrap_or(s);
+
+    let (sign, digits) = match s.chars().next()? {
+        '+' => (1, &s[1..]),
+        '-' => (-1, &s[1..]),
+        _ => return None,
+    };
+
+    if !digits.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+        return None;
+    }
+
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if digits.len() > 2 => digits.split_at(digits.len() - 2),
+        None => (digits, "0"),
+    };
+
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+
+    if hours > 25 || minutes > 59 {
+        return None;
+    }
+
+    Offset::from_seconds(sign * (hours * 3600 + minutes * 60)).ok()
+}
+
+impl FromStr for Timezone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+
+        let time_zone = match name {
+            "" => bail!("timezone is empty"),
+            "UTC" | "Z" | "utc" | "z" => TimeZone::UTC,
+            _ => match parse_offset(name) {
+                Some(offset) => TimeZone::fixed(offset),
+                None => TimeZone::get(name).with_context(|| {
+                    format!(
+                        "unknown timezone: {name}, expected an IANA name or an offset like +08:00"
+                    )
+                })?,
+            },
+        };
+
+        Ok(Timezone {
+            name: name.to_string(),
+            time_zone,
+        })
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(value: Timezone) -> Self {
+        value.name
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Timestamp of an RFC 3339 string, or of a civil datetime taken as UTC.
+pub(crate) fn parse_timestamp(value: &str) -> Option<Timestamp> {
+    value.parse::<Timestamp>().ok().or_else(|| {
+        value
+            .parse::<civil::DateTime>()
+            .ok()
+            .and_then(|datetime| datetime.to_zoned(TimeZone::UTC).ok())
+            .map(|zoned| zoned.timestamp())
+    })
+}
+
+/// RFC 3339 datetime with the offset of the zone, e.g. `2025-08-08T12:03:05+02:00`.
+pub(crate) fn rfc3339(zoned: &Zoned) -> String {
+    zoned.strftime("%Y-%m-%dT%H:%M:%S%.f%:z").to_string()
+}
+
+/// First day of the period of `date` and of the following period.
+fn period(date: Date, period: &str) -> Option<(Date, Date)> {
+    let (start, length) = match period {
+        "today" => (date, 1.day()),
+        "this_week" => (
+            date.checked_sub(i64::from(date.weekday().to_monday_zero_offset()).days())
+                .ok()?,
+            1.week(),
+        ),
+        "this_month" => (date.first_of_month(), 1.month()),
+        "this_quarter" => (
+            Date::new(date.year(), (date.month() - 1) / 3 * 3 + 1, 1).ok()?,
+            3.months(),
+        ),
+        "this_year" => (date.first_of_year(), 1.year()),
+        _ => return None,
+    };
+
+    Some((start, start.checked_add(length).ok()?))
+}
+
+/// Local range a relative date operator such as `before_this_week` covers at
+/// `now`, e.g. `..2025-08-04T00:00:00+02:00`. Dates only for date attributes.
+pub(crate) fn relative_range(
+    operator: &str,
+    timezone: &Timezone,
+    now: Timestamp,
+    date_only: bool,
+) -> Option<String> {
+    let (relation, name) = match operator.split_once('_') {
+        Some(("before", name)) => ("before", name),
+        Some(("after", name)) => ("after", name),
+        _ => ("in", operator),
+    };
+    let (start, end) = period(timezone.to_zoned(now).date(), name)?;
+
+    let bound = |date: Date| match date_only {
+        true => Some(date.to_string()),
+        false => timezone.start_of(date).map(|zoned| rfc3339(&zoned)),
+    };
+
+    Some(match relation {
+        "before" => format!("..{}", bound(start)?),
+        "after" => format!("{}..", bound(end)?),
+        _ => format!("{}..{}", bound(start)?, bound(end)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_ranges_in_timezone() {
+        let timezone: Timezone = "+08:00".parse().unwrap();
+        // Friday evening in UTC, already Saturday at +08:00.
+        let now: Timestamp = "2025-08-08T20:00:00Z".parse().unwrap();
+        let range = |operator, date_only| relative_range(operator, &timezone, now, date_only);
+
+        assert_eq!(
+            range("today", true).as_deref(),
+            Some("2025-08-09..2025-08-10")
+        );
+        assert_eq!(
+            range("before_this_week", false).as_deref(),
+            Some("..2025-08-04T00:00:00+08:00")
+        );
+        assert_eq!(
+            range("after_this_month", true).as_deref(),
+            Some("2025-09-01..")
+        );
+        assert_eq!(
+            range("this_quarter", true).as_deref(),
+            Some("2025-07-01..2025-10-01")
+        );
+        assert_eq!(range("is_present", true), None);
+
+        let utc = relative_range("today", &Timezone::utc(), now, true);
+
+        assert_eq!(utc.as_deref(), Some("2025-08-08..2025-08-09"));
+    }
+}
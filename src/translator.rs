@@ -0,0 +1,508 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    io::{Read, Write},
+    ops::Index,
+    str::FromStr,
+};
+
+use anyhow::{Error, Result, bail};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use serde_json::{Map, Value};
+
+use crate::{
+    AttributeOrder, FormatterTarget, KeyStyle, LookupMatch, ObjectAttribute, Options, Payload,
+    Response, Timezone, ValueFormatter, lookup,
+    parse::{merge_reference, object_entity, payload, response},
+    query,
+    stream::{EntityWriter, StreamFormat},
+};
+
+/// Translated entity, its attributes in the order of [`AttributeOrder`]. Keys
+/// are borrowed from the reference of the [`Translator`] unless the key style
+/// combines parts, see [`Entity::into_owned`] to keep an entity longer.
+#[derive(Clone, Default, PartialEq)]
+pub struct Entity<'r>(Vec<(Cow<'r, str>, Value)>);
+
+impl<'r> Entity<'r> {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(key, value)| (key.as_ref(), value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(key, _)| key.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Entity with owned keys, no longer tied to the reference.
+    pub fn into_owned(self) -> Entity<'static> {
+        Entity(
+            self.0
+                .into_iter()
+                .map(|(key, value)| (Cow::Owned(key.into_owned()), value))
+                .collect(),
+        )
+    }
+}
+
+impl<'r> From<Vec<(Cow<'r, str>, Value)>> for Entity<'r> {
+    fn from(attributes: Vec<(Cow<'r, str>, Value)>) -> Self {
+        Entity(attributes)
+    }
+}
+
+impl From<Vec<(String, Value)>> for Entity<'static> {
+    fn from(attributes: Vec<(String, Value)>) -> Self {
+        Entity(
+            attributes
+                .into_iter()
+                .map(|(key, value)| (Cow::Owned(key), value))
+                .collect(),
+        )
+    }
+}
+
+impl From<Entity<'_>> for HashMap<String, Value> {
+    fn from(entity: Entity) -> Self {
+        entity
+            .0
+            .into_iter()
+            .map(|(key, value)| (key.into_owned(), value))
+            .collect()
+    }
+}
+
+impl From<Entity<'_>> for Value {
+    fn from(entity: Entity) -> Self {
+        Value::Object(
+            entity
+                .0
+                .into_iter()
+                .map(|(key, value)| (key.into_owned(), value))
+                .collect::<Map<String, Value>>(),
+        )
+    }
+}
+
+impl<'r> IntoIterator for Entity<'r> {
+    type Item = (Cow<'r, str>, Value);
+    type IntoIter = std::vec::IntoIter<(Cow<'r, str>, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Value of a key, `null` when missing like indexing a json object.
+impl Index<&str> for Entity<'_> {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        static NULL: Value = Value::Null;
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Debug for Entity<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Serialize for Entity<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+/// Translated payload, parts missing from the payload are `None`.
+#[derive(Clone, Default, PartialEq, Serialize)]
+pub struct TranslatedPayload<'r> {
+    /// Requested attributes as `name, id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_attributes: Option<Vec<String>>,
+    /// Search query as a lisp like expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_entity_attribute_values: Option<Entity<'r>>,
+}
+
+impl TranslatedPayload<'_> {
+    /// Payload with owned keys, no longer tied to the reference.
+    pub fn into_owned(self) -> TranslatedPayload<'static> {
+        TranslatedPayload {
+            object_attributes: self.object_attributes,
+            search_query: self.search_query,
+            object_entity_attribute_values: self
+                .object_entity_attribute_values
+                .map(Entity::into_owned),
+        }
+    }
+}
+
+/// Parts present in the payload, like its json.
+impl Debug for TranslatedPayload<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+
+        if let Some(object_attributes) = &self.object_attributes {
+            map.entry(&"object_attributes", object_attributes);
+        }
+
+        if let Some(search_query) = &self.search_query {
+            map.entry(&"search_query", search_query);
+        }
+
+        if let Some(entity) = &self.object_entity_attribute_values {
+            map.entry(&"object_entity_attribute_values", entity);
+        }
+
+        map.finish()
+    }
+}
+
+impl From<TranslatedPayload<'_>> for HashMap<String, Value> {
+    fn from(payload: TranslatedPayload) -> Self {
+        let mut map = HashMap::new();
+
+        if let Some(object_attributes) = payload.object_attributes {
+            map.insert("object_attributes".to_string(), object_attributes.into());
+        }
+
+        if let Some(search_query) = payload.search_query {
+            map.insert("search_query".to_string(), search_query.into());
+        }
+
+        if let Some(entity) = payload.object_entity_attribute_values {
+            map.insert("object_entity_attribute_values".to_string(), entity.into());
+        }
+
+        map
+    }
+}
+
+/// Kind of a json document to translate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputKind {
+    Payload,
+    Response,
+    Entity,
+}
+
+impl InputKind {
+    /// Guess the kind from the shape of a document: responses have a `data`
+    /// array, payloads a search query, attribute ids or attribute values and
+    /// entities `oa_` keys.
+    pub fn detect(input: &Value) -> Option<InputKind> {
+        let input = input.as_object()?;
+
+        if input.get("data").is_some_and(Value::is_array) {
+            Some(InputKind::Response)
+        } else if [
+            "object_attribute_ids",
+            "search_query",
+            "object_entity_attribute_values",
+        ]
+        .iter()
+        .any(|key| input.contains_key(*key))
+        {
+            Some(InputKind::Payload)
+        } else if input.keys().any(|key| key.starts_with("oa_")) {
+            Some(InputKind::Entity)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for InputKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "payload" => Ok(InputKind::Payload),
+            "response" => Ok(InputKind::Response),
+            "entity" => Ok(InputKind::Entity),
+            _ => bail!("unknown input kind: {s}, expected one of payload, response, entity"),
+        }
+    }
+}
+
+impl Display for InputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputKind::Payload => write!(f, "payload"),
+            InputKind::Response => write!(f, "response"),
+            InputKind::Entity => write!(f, "entity"),
+        }
+    }
+}
+
+/// Translation of a document of any kind, serialized like its content.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Translated<'r> {
+    Payload(TranslatedPayload<'r>),
+    Response(Vec<Entity<'r>>),
+    Entity(Entity<'r>),
+}
+
+impl Translated<'_> {
+    pub fn kind(&self) -> InputKind {
+        match self {
+            Translated::Payload(_) => InputKind::Payload,
+            Translated::Response(_) => InputKind::Response,
+            Translated::Entity(_) => InputKind::Entity,
+        }
+    }
+
+    /// Translation with owned keys, no longer tied to the reference.
+    pub fn into_owned(self) -> Translated<'static> {
+        match self {
+            Translated::Payload(payload) => Translated::Payload(payload.into_owned()),
+            Translated::Response(entities) => {
+                Translated::Response(entities.into_iter().map(Entity::into_owned).collect())
+            }
+            Translated::Entity(entity) => Translated::Entity(entity.into_owned()),
+        }
+    }
+}
+
+/// Translates payloads, responses and entities of one or more references.
+#[derive(Default)]
+pub struct Translator {
+    reference: HashMap<String, ObjectAttribute>,
+    options: Options,
+}
+
+impl Translator {
+    /// Translator of one reference with default options.
+    pub fn new(reference: HashMap<String, ObjectAttribute>) -> Self {
+        Translator::builder().reference(reference).build()
+    }
+
+    pub fn builder() -> TranslatorBuilder {
+        TranslatorBuilder::default()
+    }
+
+    /// Add the attributes of another reference, attributes already known are kept.
+    pub fn add_reference(&mut self, reference: HashMap<String, ObjectAttribute>) {
+        merge_reference(&mut self.reference, reference);
+    }
+
+    pub fn reference(&self) -> &HashMap<String, ObjectAttribute> {
+        &self.reference
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    pub fn payload(&self, payload: Payload) -> Result<TranslatedPayload<'_>> {
+        payload::translate(payload, &self.reference, &self.options)
+    }
+
+    pub fn response(&self, response: Response) -> Result<Vec<Entity<'_>>> {
+        response::translate(response, &self.reference, &self.options)
+    }
+
+    pub fn entity(&self, entity: HashMap<String, Value>) -> Result<Entity<'_>> {
+        object_entity::translate(entity, &self.reference, &self.options)
+    }
+
+    /// Translate a payload kept by the caller without cloning it first. Keys
+    /// of the result borrow from the reference, values are owned json and
+    /// those passed through unchanged are copied.
+    pub fn payload_value(&self, payload: &Value) -> Result<TranslatedPayload<'_>> {
+        payload::translate_borrowed(payload, &self.reference, &self.options)
+    }
+
+    /// Translate a response kept by the caller, see [`Translator::payload_value`].
+    pub fn response_value(&self, response: &Value) -> Result<Vec<Entity<'_>>> {
+        response::translate_borrowed(response, &self.reference, &self.options)
+    }
+
+    /// Translate an entity kept by the caller, see [`Translator::payload_value`].
+    pub fn entity_value(&self, entity: &Value) -> Result<Entity<'_>> {
+        object_entity::translate_borrowed(entity, &self.reference, &self.options)
+    }
+
+    /// Translate a response while reading it, see [`Translator::write_response`].
+    pub fn response_stream<'r>(
+        &'r self,
+        reader: impl Read,
+        each: impl FnMut(Entity<'r>) -> Result<()>,
+    ) -> Result<usize> {
+        response::translate_stream(reader, &self.reference, &self.options, each)
+    }
+
+    /// Translate a response while reading it and write each entity as it is
+    /// translated, memory stays flat however large the response is. Returns
+    /// the number of entities. On an error part-way the array is still
+    /// closed, so the output holds the entities translated until then.
+    pub fn write_response(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        format: StreamFormat,
+    ) -> Result<usize> {
+        let mut writer = EntityWriter::new(writer, format);
+        let result = self.response_stream(reader, |entity| writer.write(&entity));
+        let count = writer.finish()?;
+
+        result.map(|_| count)
+    }
+
+    /// Translate payload json text, parsed into a [`Value`] first like
+    /// [`Translator::payload_value`] takes.
+    pub fn payload_str(&self, payload: &str) -> Result<TranslatedPayload<'_>> {
+        self.payload_value(&serde_json::from_str(payload)?)
+    }
+
+    /// Translate response json text, parsed into a [`Value`] first.
+    pub fn response_str(&self, response: &str) -> Result<Vec<Entity<'_>>> {
+        self.response_value(&serde_json::from_str(response)?)
+    }
+
+    /// Translate entity json text, parsed into a [`Value`] first.
+    pub fn entity_str(&self, entity: &str) -> Result<Entity<'_>> {
+        self.entity_value(&serde_json::from_str(entity)?)
+    }
+
+    /// Translate a payload, response or entity, its kind detected by
+    /// [`InputKind::detect`].
+    pub fn translate_value(&self, input: &Value) -> Result<Translated<'_>> {
+        match InputKind::detect(input) {
+            Some(kind) => self.translate_kind(kind, input),
+            None => bail!("unknown input, expected a payload, response or entity"),
+        }
+    }
+
+    /// Translate a document of a known kind.
+    pub fn translate_kind(&self, kind: InputKind, input: &Value) -> Result<Translated<'_>> {
+        Ok(match kind {
+            InputKind::Payload => Translated::Payload(self.payload_value(input)?),
+            InputKind::Response => Translated::Response(self.response_value(input)?),
+            InputKind::Entity => Translated::Entity(self.entity_value(input)?),
+        })
+    }
+
+    /// Payload of a search query written like a translated one, see [`crate::query_to_payload`].
+    pub fn query_payload(&self, query: &str) -> Result<Value> {
+        query::parse(&self.reference, query)
+    }
+
+    /// Attributes and picklist options by full or partial id, name or label, best first.
+    pub fn lookup(&self, query: &str, limit: usize) -> Vec<LookupMatch> {
+        lookup(&self.reference, query, limit)
+    }
+}
+
+/// Builder of a [`Translator`], see [`Options`] for what each setting does.
+#[derive(Default)]
+pub struct TranslatorBuilder {
+    translator: Translator,
+}
+
+impl TranslatorBuilder {
+    /// Add a reference, attributes of earlier references win on duplicate ids.
+    pub fn reference(mut self, reference: HashMap<String, ObjectAttribute>) -> Self {
+        self.translator.add_reference(reference);
+        self
+    }
+
+    /// Replace all options at once.
+    pub fn options(mut self, options: Options) -> Self {
+        self.translator.options = options;
+        self
+    }
+
+    pub fn key_style(mut self, key_style: KeyStyle) -> Self {
+        self.translator.options.key_style = key_style;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.translator.options.strict = strict;
+        self
+    }
+
+    pub fn order(mut self, order: AttributeOrder) -> Self {
+        self.translator.options.order = order;
+        self
+    }
+
+    pub fn redact(mut self, selector: impl Into<String>) -> Self {
+        self.translator.options.redact.push(selector.into());
+        self
+    }
+
+    pub fn template(mut self, selector: impl Into<String>, template: impl Into<String>) -> Self {
+        self.translator
+            .options
+            .templates
+            .push((selector.into(), template.into()));
+        self
+    }
+
+    pub fn formatter(
+        mut self,
+        target: FormatterTarget,
+        formatter: impl ValueFormatter + 'static,
+    ) -> Self {
+        self.translator
+            .options
+            .register_formatter(target, formatter);
+        self
+    }
+
+    pub fn display(mut self, display: bool) -> Self {
+        self.translator.options.display = display;
+        self
+    }
+
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.translator.options.currency = Some(currency.into());
+        self
+    }
+
+    pub fn timezone(mut self, timezone: Timezone) -> Self {
+        self.translator.options.timezone = Some(timezone);
+        self
+    }
+
+    pub fn keep_original(mut self, keep_original: bool) -> Self {
+        self.translator.options.keep_original = keep_original;
+        self
+    }
+
+    pub fn build(self) -> Translator {
+        self.translator
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+use crate::parse::{ObjectAttributeDataType, reference::ObjectAttributesRaw};
+
+/// Problems found in a raw object_attributes reference.
+#[derive(Default)]
+pub struct ReferenceReport {
+    /// Picklist relationships pointing at ids missing from `included`.
+    pub missing_options: Vec<MissingOption>,
+    /// Attribute ids present more than once, only the first one is used.
+    pub duplicate_ids: Vec<String>,
+    /// Attribute names shared by several attributes of a class.
+    pub duplicate_names: Vec<Duplicate>,
+    /// Attribute labels shared by several attributes of a class.
+    pub duplicate_labels: Vec<Duplicate>,
+    /// Picklist attributes without any option.
+    pub empty_picklists: Vec<String>,
+    /// Included options no attribute refers to.
+    pub orphaned_options: Vec<String>,
+}
+
+pub struct MissingOption {
+    pub object_attribute_id: String,
+    pub option_id: String,
+}
+
+pub struct Duplicate {
+    pub object_class_id: Option<String>,
+    pub value: String,
+    pub object_attribute_ids: Vec<String>,
+}
+
+impl ReferenceReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing_options.is_empty()
+            && self.duplicate_ids.is_empty()
+            && self.duplicate_names.is_empty()
+            && self.duplicate_labels.is_empty()
+            && self.empty_picklists.is_empty()
+            && self.orphaned_options.is_empty()
+    }
+}
+
+impl Display for ReferenceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            return write!(f, "No problems found.");
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+
+        for missing in &self.missing_options {
+            lines.push(format!(
+                "missing option: {} referenced by {}",
+                missing.option_id, missing.object_attribute_id
+            ));
+        }
+
+        for id in &self.duplicate_ids {
+            lines.push(format!("duplicate attribute id: {id}"));
+        }
+
+        for (kind, duplicates) in [
+            ("name", &self.duplicate_names),
+            ("label", &self.duplicate_labels),
+        ] {
+            for duplicate in duplicates {
+                let class = match &duplicate.object_class_id {
+                    Some(id) => format!(" in class {id}"),
+                    None => String::new(),
+                };
+
+                lines.push(format!(
+                    "duplicate attribute {kind}{class}: {} used by {}",
+                    duplicate.value,
+                    duplicate.object_attribute_ids.join(", ")
+                ));
+            }
+        }
+
+        for id in &self.empty_picklists {
+            lines.push(format!("picklist without options: {id}"));
+        }
+
+        for id in &self.orphaned_options {
+            lines.push(format!("orphaned option: {id}"));
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Group attribute ids by class and `key`, keeping groups with more than one id.
+fn find_duplicates<'a>(
+    attributes: impl Iterator<Item = (Option<&'a str>, &'a str, &'a str)>,
+) -> Vec<Duplicate> {
+    let mut groups: BTreeMap<(Option<&str>, &str), Vec<String>> = BTreeMap::new();
+
+    for (object_class_id, key, id) in attributes {
+        if !key.is_empty() {
+            groups
+                .entry((object_class_id, key))
+                .or_default()
+                .push(id.to_string());
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(
+            |((object_class_id, value), object_attribute_ids)| Duplicate {
+                object_class_id: object_class_id.map(str::to_string),
+                value: value.to_string(),
+                object_attribute_ids,
+            },
+        )
+        .collect()
+}
+
+pub fn validate(reference: &ObjectAttributesRaw) -> ReferenceReport {
+    let mut report = ReferenceReport::default();
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    let mut referenced_options: HashSet<&str> = HashSet::new();
+    let mut attributes = Vec::with_capacity(reference.data.len());
+
+    for oa in &reference.data {
+        let options = oa
+            .relationships
+            .picklist_options
+            .data
+            .as_deref()
+            .unwrap_or_default();
+
+        // Options of a skipped duplicate are still referenced, not orphaned.
+        referenced_options.extend(options.iter().map(|option| option.id.as_str()));
+
+        if !seen_ids.insert(&oa.id) {
+            if !report.duplicate_ids.contains(&oa.id) {
+                report.duplicate_ids.push(oa.id.clone());
+            }
+            continue;
+        }
+
+        attributes.push(oa);
+
+        if oa.attributes.data_type == ObjectAttributeDataType::Picklist && options.is_empty() {
+            report.empty_picklists.push(oa.id.clone());
+        }
+
+        for option in options {
+            if !reference.included.contains_key(&option.id) {
+                report.missing_options.push(MissingOption {
+                    object_attribute_id: oa.id.clone(),
+                    option_id: option.id.clone(),
+                });
+            }
+        }
+    }
+
+    report.duplicate_names = find_duplicates(attributes.iter().map(|oa| {
+        (
+            oa.attributes.object_class_id.as_deref(),
+            oa.attributes.name.as_str(),
+            oa.id.as_str(),
+        )
+    }));
+    report.duplicate_labels = find_duplicates(attributes.iter().map(|oa| {
+        (
+            oa.attributes.object_class_id.as_deref(),
+            oa.attributes.label.as_str(),
+            oa.id.as_str(),
+        )
+    }));
+
+    report.orphaned_options = reference
+        .included
+        .keys()
+        .filter(|id| !referenced_options.contains(id.as_str()))
+        .cloned()
+        .collect();
+    report.orphaned_options.sort_unstable();
+
+    report
+}
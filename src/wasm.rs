@@ -4,45 +4,151 @@ use serde_json::Value;
 use wasm_bindgen::prelude::*;
 
 use super::{
-    ObjectAttribute, Payload, Response, parse::reference::ObjectAttributesRaw,
-    preprocess_reference, process_object_entity, process_payload, process_response,
+    AttributeOrder, KeyStyle, ObjectAttribute, Payload, Response, Timezone, Translator,
+    build_snapshot, generate_typescript, load_snapshot, parse::reference::ObjectAttributesRaw,
+    preprocess_reference,
 };
 
+fn parse_reference(reference: JsValue) -> Result<HashMap<String, ObjectAttribute>, String> {
+    let reference_struct: ObjectAttributesRaw =
+        serde_wasm_bindgen::from_value(reference).map_err(|e| e.to_string())?;
+
+    preprocess_reference(reference_struct).map_err(|e| e.to_string())
+}
+
+/// Thin wrapper of a [`Translator`], so js and rust translate alike.
 #[wasm_bindgen]
 pub struct Processor {
-    hashmap: HashMap<String, ObjectAttribute>,
+    translator: Translator,
 }
 
 #[wasm_bindgen]
 impl Processor {
     #[wasm_bindgen(constructor)]
     pub fn new(reference: JsValue) -> Result<Processor, String> {
-        let reference_struct: ObjectAttributesRaw =
-            serde_wasm_bindgen::from_value(reference).map_err(|e| e.to_string())?;
+        Ok(Processor {
+            translator: Translator::new(parse_reference(reference)?),
+        })
+    }
+
+    /// Build a processor from a snapshot made by `snapshot()` or the cli.
+    #[wasm_bindgen(js_name = fromSnapshot)]
+    pub fn from_snapshot(snapshot: &str) -> Result<Processor, String> {
+        let hashmap = load_snapshot(snapshot).map_err(|e| e.to_string())?;
+
+        Ok(Processor {
+            translator: Translator::new(hashmap),
+        })
+    }
+
+    /// Add the attributes of another reference, attributes already known are kept.
+    #[wasm_bindgen(js_name = addReference)]
+    pub fn add_reference(&mut self, reference: JsValue) -> Result<(), String> {
+        self.translator.add_reference(parse_reference(reference)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn snapshot(&self) -> Result<String, String> {
+        build_snapshot(self.translator.reference()).map_err(|e| e.to_string())
+    }
+
+    /// One of `label`, `name`, `name-type`, `id` or `label-id`.
+    #[wasm_bindgen(js_name = setKeyStyle)]
+    pub fn set_key_style(&mut self, key_style: &str) -> Result<(), String> {
+        self.translator.options_mut().key_style =
+            key_style.parse::<KeyStyle>().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Fail on unknown attributes and picklist options instead of warning.
+    #[wasm_bindgen(js_name = setStrict)]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.translator.options_mut().strict = strict;
+    }
 
-        let hashmap = preprocess_reference(reference_struct).map_err(|e| e.to_string())?;
+    /// One of `key`, `reference` or `id`.
+    #[wasm_bindgen(js_name = setOrder)]
+    pub fn set_order(&mut self, order: &str) -> Result<(), String> {
+        self.translator.options_mut().order =
+            order.parse::<AttributeOrder>().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Replace values of an attribute by `[redacted]`, by id, name, label or `type:<data type>`.
+    #[wasm_bindgen]
+    pub fn redact(&mut self, selector: String) {
+        self.translator.options_mut().redact.push(selector);
+    }
+
+    /// Format values by data type, e.g. dates, percentages and booleans as yes/no.
+    #[wasm_bindgen(js_name = setDisplay)]
+    pub fn set_display(&mut self, display: bool) {
+        self.translator.options_mut().display = display;
+    }
+
+    /// Currency code of amounts, e.g. `EUR`.
+    #[wasm_bindgen(js_name = setCurrency)]
+    pub fn set_currency(&mut self, currency: Option<String>) {
+        self.translator.options_mut().currency = currency;
+    }
 
-        Ok(Processor { hashmap })
+    /// IANA name such as `Europe/Berlin` or offset like `+08:00`, UTC when unset.
+    #[wasm_bindgen(js_name = setTimezone)]
+    pub fn set_timezone(&mut self, timezone: Option<String>) -> Result<(), String> {
+        self.translator.options_mut().timezone = timezone
+            .map(|timezone| timezone.parse::<Timezone>())
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Show changed values as `{ value, original }`.
+    #[wasm_bindgen(js_name = setKeepOriginal)]
+    pub fn set_keep_original(&mut self, keep_original: bool) {
+        self.translator.options_mut().keep_original = keep_original;
+    }
+
+    /// Attributes and picklist options matching an id fragment, name or label, best first.
+    #[wasm_bindgen]
+    pub fn lookup(&self, query: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let matches = self.translator.lookup(query, limit.unwrap_or(10));
+        serde_wasm_bindgen::to_value(&matches).map_err(|e| e.to_string().into())
+    }
+
+    /// TypeScript interfaces of entities, named `ObjectEntity` unless `name` is given.
+    #[wasm_bindgen]
+    pub fn typescript(&self, name: Option<String>) -> String {
+        generate_typescript(
+            self.translator.reference(),
+            name.as_deref().unwrap_or("ObjectEntity"),
+        )
     }
 
     #[wasm_bindgen]
     pub fn payload(&self, payload: JsValue) -> Result<JsValue, JsValue> {
         let payload: Payload = serde_wasm_bindgen::from_value(payload)?;
-        let result = process_payload(&self.hashmap, payload).map_err(|e| e.to_string())?;
+        let result = self
+            .translator
+            .payload(payload)
+            .map_err(|e| e.to_string())?;
         serde_wasm_bindgen::to_value(&result).map_err(|e| e.to_string().into())
     }
 
     #[wasm_bindgen]
     pub fn response(&self, response: JsValue) -> Result<JsValue, JsValue> {
         let response: Response = serde_wasm_bindgen::from_value(response)?;
-        let result = process_response(&self.hashmap, response).map_err(|e| e.to_string())?;
+        let result = self
+            .translator
+            .response(response)
+            .map_err(|e| e.to_string())?;
         serde_wasm_bindgen::to_value(&result).map_err(|e| e.to_string().into())
     }
 
     #[wasm_bindgen]
     pub fn entity(&self, object_entity: JsValue) -> Result<JsValue, JsValue> {
         let entity: HashMap<String, Value> = serde_wasm_bindgen::from_value(object_entity)?;
-        let result = process_object_entity(&self.hashmap, entity).map_err(|e| e.to_string())?;
+        let result = self.translator.entity(entity).map_err(|e| e.to_string())?;
         serde_wasm_bindgen::to_value(&result).map_err(|e| e.to_string().into())
     }
 }
@@ -1,9 +1,77 @@
 use std::{collections::HashMap, fs};
 
 use json_replacer::{ObjectAttribute, preprocess_reference};
+use serde_json::{Map, Value, json};
 
 pub fn setup() -> HashMap<String, ObjectAttribute> {
-    let content = fs::read_to_string("tests/oa.json").unwrap();
-    let reference = serde_json::from_str(&content).unwrap();
-    preprocess_reference(reference).expect("failed to parse reference file")
+    parse_reference(&fs::read_to_string("tests/oa.json").unwrap())
+}
+
+/// Parsed reference of json text, e.g. `tests/oa.json` with edits.
+pub fn parse_reference(content: &str) -> HashMap<String, ObjectAttribute> {
+    preprocess_reference(serde_json::from_str(content).unwrap())
+        .expect("failed to parse reference file")
+}
+
+/// Raw reference of attributes and picklist options, options keyed by id.
+pub fn raw_reference(
+    attributes: impl IntoIterator<Item = Value>,
+    options: impl IntoIterator<Item = Value>,
+) -> Value {
+    let included: Map<String, Value> = options
+        .into_iter()
+        .map(|option| (option["id"].as_str().unwrap().to_string(), option))
+        .collect();
+
+    json!({ "data": attributes.into_iter().collect::<Vec<Value>>(), "included": included })
+}
+
+/// Parsed reference of attributes and picklist options.
+pub fn reference(
+    attributes: impl IntoIterator<Item = Value>,
+    options: impl IntoIterator<Item = Value>,
+) -> HashMap<String, ObjectAttribute> {
+    preprocess_reference(serde_json::from_value(raw_reference(attributes, options)).unwrap())
+        .expect("failed to parse reference")
+}
+
+/// Attribute without picklist options, labelled like its name.
+pub fn attribute(id: &str, data_type: &str, name: &str) -> Value {
+    json!({
+        "type": "ObjectAttribute",
+        "id": id,
+        "attributes": { "data_type": data_type, "label": name, "name": name },
+        "relationships": { "picklist_options": { "data": null } }
+    })
+}
+
+/// Picklist attribute with options by id.
+pub fn picklist(id: &str, name: &str, options: &[&str]) -> Value {
+    let mut picklist = attribute(id, "picklist", name);
+    picklist["relationships"]["picklist_options"]["data"] = options
+        .iter()
+        .map(|id| json!({ "id": id, "type": "PicklistOption" }))
+        .collect();
+
+    picklist
+}
+
+/// Nested form attribute with its children by id.
+pub fn nested_form(id: &str, name: &str, children: &[&str]) -> Value {
+    let mut form = attribute(id, "nested_form", name);
+    form["relationships"]["object_attributes"]["data"] = children
+        .iter()
+        .map(|id| json!({ "id": id, "type": "ObjectAttribute" }))
+        .collect();
+
+    form
+}
+
+/// Enabled picklist option, labelled like its name.
+pub fn option(id: &str, name: &str) -> Value {
+    json!({
+        "type": "PicklistOption",
+        "id": id,
+        "attributes": { "disabled": false, "label": name, "name": name }
+    })
 }
@@ -4,7 +4,15 @@ use std::{collections::HashMap, fs, path::Path};
 
 use serde_json::Value;
 
-use json_replacer::{Payload, Response, process_object_entity, process_payload, process_response};
+use json_replacer::{
+    AttributeOrder, DictionaryFormat, FormatterTarget, InputKind, JsonSchemaTarget, KeyStyle,
+    LookupKind, ObjectAttribute, ObjectAttributeDataType, Options, Payload, Response, SchemaFilter,
+    SchemaFormat, StreamFormat, Timezone, Translator, ValueFormatter, build_snapshot,
+    diff_references, generate_json_schema, generate_rust, generate_typescript, load_snapshot,
+    lookup, merge_references, process_object_entity, process_object_entity_with_options,
+    process_payload, process_payload_with_options, process_response, query_to_payload,
+    render_dictionary, render_schema, validate_reference,
+};
 
 fn get_content(input: String) -> String {
     let trimmed_input = input.trim();
@@ -83,3 +91,1142 @@ fn parse_object_entity() {
 
     assert_eq!(entity.len(), 3);
 }
+
+#[test]
+fn parse_unknown_data_type() {
+    let reference = common::reference(
+        [common::attribute(
+            "019883f0-c110-7bc5-854e-26a7135a9ec1",
+            "formula",
+            "Total",
+        )],
+        [],
+    );
+
+    let object_entity = serde_json::from_str::<HashMap<String, Value>>(
+        r#"{ "oa_019883f0_c110_7bc5_854e_26a7135a9ec1": "1 + 1" }"#,
+    )
+    .unwrap();
+
+    let entity =
+        process_object_entity(&reference, object_entity).expect("failed to parse object entity");
+
+    assert_eq!(
+        entity.get("Total (formula)"),
+        Some(&Value::String("1 + 1".to_string()))
+    );
+}
+
+#[test]
+fn parse_object_entity_with_label_key_style() {
+    let reference = common::setup();
+
+    let object_entity_content = get_content("tests/object_entity.json".to_string());
+    let object_entity =
+        serde_json::from_str::<HashMap<String, Value>>(&object_entity_content).unwrap();
+
+    let options = Options {
+        key_style: KeyStyle::Label,
+        ..Options::default()
+    };
+    let entity = process_object_entity_with_options(&reference, object_entity, &options)
+        .expect("failed to parse object entity");
+
+    assert_eq!(
+        entity.get("Type Name"),
+        Some(&Value::String("Assignment".to_string()))
+    );
+}
+
+#[test]
+fn picklist_option_metadata() {
+    let reference = common::reference(
+        [common::picklist(
+            "019883f0-c110-7bc5-854e-26a7135a9ec2",
+            "Stage",
+            &["option-b", "option-a"],
+        )],
+        [
+            serde_json::json!({
+                "type": "PicklistOption",
+                "id": "option-a",
+                "attributes": { "disabled": true, "label": "Won", "name": "Won", "position": 1 }
+            }),
+            serde_json::json!({
+                "type": "PicklistOption",
+                "id": "option-b",
+                "attributes": {
+                    "disabled": false,
+                    "label": "Lost",
+                    "name": "Lost <late> & gone",
+                    "position": 2,
+                    "color": "red"
+                }
+            }),
+        ],
+    );
+
+    let listing = render_schema(&reference, &SchemaFilter::default(), SchemaFormat::Table).unwrap();
+    let won = listing.find("Won").unwrap();
+    let lost = listing.find("Lost").unwrap();
+
+    assert!(won < lost);
+
+    let option_line = |id: &str| listing.lines().find(|line| line.contains(id)).unwrap();
+
+    assert!(option_line("option-a").ends_with("[disabled]"));
+    assert!(!option_line("option-b").contains("[disabled]"));
+    assert!(listing.contains(r#"{"color":"red"}"#));
+
+    let markdown =
+        render_schema(&reference, &SchemaFilter::default(), SchemaFormat::Markdown).unwrap();
+
+    assert!(markdown.contains("Lost &lt;late&gt; &amp; gone (`option-b`)"));
+    assert!(!markdown.contains("<late>"));
+
+    let object_entity = serde_json::from_str::<HashMap<String, Value>>(
+        r#"{ "oa_019883f0_c110_7bc5_854e_26a7135a9ec2": "option-a" }"#,
+    )
+    .unwrap();
+    let entity =
+        process_object_entity(&reference, object_entity).expect("failed to parse object entity");
+
+    assert_eq!(
+        entity.get("Stage (picklist)"),
+        Some(&Value::String("Won (disabled)".to_string()))
+    );
+}
+
+#[test]
+fn shared_picklist_options() {
+    let reference = common::reference(
+        [
+            common::picklist(
+                "019883f0-c110-7bc5-854e-26a7135a9ec3",
+                "First",
+                &["option-a"],
+            ),
+            common::picklist(
+                "019883f0-c110-7bc5-854e-26a7135a9ec4",
+                "Second",
+                &["option-a"],
+            ),
+        ],
+        [common::option("option-a", "Shared")],
+    );
+
+    let object_entity = serde_json::from_str::<HashMap<String, Value>>(
+        r#"{
+            "oa_019883f0_c110_7bc5_854e_26a7135a9ec3": "option-a",
+            "oa_019883f0_c110_7bc5_854e_26a7135a9ec4": "option-a"
+        }"#,
+    )
+    .unwrap();
+    let entity =
+        process_object_entity(&reference, object_entity).expect("failed to parse object entity");
+
+    assert_eq!(
+        entity.get("First (picklist)"),
+        entity.get("Second (picklist)")
+    );
+    assert_eq!(
+        entity.get("First (picklist)"),
+        Some(&Value::String("Shared".to_string()))
+    );
+}
+
+#[test]
+fn validate_reference_report() {
+    let mut phase = common::picklist("019883f0-c110-7bc5-854e-26a7135a9ec6", "Phase", &[]);
+    phase["attributes"]["label"] = "Stage".into();
+    let mut copy = common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec6", "string", "Copy");
+    copy["relationships"]["picklist_options"]["data"] =
+        serde_json::json!([{ "id": "copied", "type": "PicklistOption" }]);
+
+    let reference = common::raw_reference(
+        [
+            common::picklist(
+                "019883f0-c110-7bc5-854e-26a7135a9ec5",
+                "Stage",
+                &["missing"],
+            ),
+            phase,
+            copy,
+        ],
+        [
+            common::option("copied", "Copied"),
+            common::option("orphan", "Orphan"),
+        ],
+    );
+    let reference = serde_json::from_value(reference).unwrap();
+
+    let report = validate_reference(&reference);
+
+    assert!(!report.is_valid());
+    assert_eq!(report.missing_options.len(), 1);
+    assert_eq!(report.missing_options[0].option_id, "missing");
+    assert_eq!(
+        report.duplicate_ids,
+        vec!["019883f0-c110-7bc5-854e-26a7135a9ec6"]
+    );
+    assert!(report.duplicate_names.is_empty());
+    assert_eq!(report.duplicate_labels.len(), 1);
+    assert_eq!(report.duplicate_labels[0].value, "Stage");
+    assert_eq!(
+        report.empty_picklists,
+        vec!["019883f0-c110-7bc5-854e-26a7135a9ec6"]
+    );
+    assert_eq!(report.orphaned_options, vec!["orphan"]);
+}
+
+#[test]
+fn diff_reference_snapshots() {
+    let old = common::setup();
+
+    let content = fs::read_to_string("tests/oa.json").unwrap();
+    let content = content
+        .replace("\"Type_Name\"", "\"Kind\"")
+        .replace("\"Factoring\"", "\"Factor\"")
+        .replace(
+            r#"{
+              "id": "019883f0-d645-7d15-a8d0-f53e3d1b6a5b",
+              "type": "PicklistOption"
+            },"#,
+            "",
+        );
+    let new = common::parse_reference(&content);
+
+    let diff = diff_references(&old, &new);
+
+    assert!(diff.added_attributes.is_empty());
+    assert_eq!(diff.renamed_attributes.len(), 1);
+    assert_eq!(diff.renamed_attributes[0].new_name, "Kind");
+    assert_eq!(diff.removed_options.len(), 1);
+    assert_eq!(diff.removed_options[0].name, "Assignment");
+    assert_eq!(diff.renamed_options.len(), 1);
+    assert_eq!(diff.renamed_options[0].new_name, "Factor");
+    assert!(diff_references(&old, &old).is_empty());
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let reference = common::setup();
+
+    let snapshot = build_snapshot(&reference).expect("failed to build snapshot");
+    let loaded = load_snapshot(&snapshot).expect("failed to load snapshot");
+
+    assert!(diff_references(&reference, &loaded).is_empty());
+
+    let object_entity_content = get_content("tests/object_entity.json".to_string());
+    let object_entity =
+        serde_json::from_str::<HashMap<String, Value>>(&object_entity_content).unwrap();
+    let entity =
+        process_object_entity(&loaded, object_entity).expect("failed to parse object entity");
+
+    assert_eq!(
+        entity.get("Type_Name (picklist)"),
+        Some(&Value::String("Assignment".to_string()))
+    );
+
+    let tampered = snapshot.replace("Assignment", "Assignmemt");
+    assert!(load_snapshot(&tampered).is_err());
+}
+
+#[test]
+fn snapshot_keeps_reference_order() {
+    let reference = common::reference(
+        [
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ed2", "string", "Zeta"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ed1", "string", "Alpha"),
+        ],
+        [],
+    );
+    let entity: HashMap<String, Value> = serde_json::from_value(serde_json::json!({
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ed1": "a",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ed2": "z"
+    }))
+    .unwrap();
+    let keys = |reference| {
+        let translator = Translator::builder()
+            .reference(reference)
+            .key_style(KeyStyle::Name)
+            .order(AttributeOrder::Reference)
+            .build();
+        let result = translator.entity(entity.clone()).unwrap();
+
+        result.keys().map(str::to_string).collect::<Vec<String>>()
+    };
+
+    let snapshot = build_snapshot(&reference).unwrap();
+    assert!(snapshot.starts_with("json-replacer-snapshot 2 "));
+    assert_eq!(keys(load_snapshot(&snapshot).unwrap()), ["Zeta", "Alpha"]);
+}
+
+#[test]
+fn parse_object_entity_with_strict_redact_and_templates() {
+    let reference = common::setup();
+
+    let object_entity_content = get_content("tests/object_entity.json".to_string());
+    let object_entity =
+        serde_json::from_str::<HashMap<String, Value>>(&object_entity_content).unwrap();
+
+    let strict = Options {
+        strict: true,
+        ..Options::default()
+    };
+    assert!(
+        process_object_entity_with_options(&reference, object_entity.clone(), &strict).is_err()
+    );
+
+    let redacted = Options {
+        redact: vec!["type:picklist".to_string()],
+        ..Options::default()
+    };
+    let entity = process_object_entity_with_options(&reference, object_entity.clone(), &redacted)
+        .expect("failed to parse object entity");
+    assert_eq!(
+        entity.get("Type_Name (picklist)"),
+        Some(&Value::String("[redacted]".to_string()))
+    );
+
+    let templated = Options {
+        templates: vec![("Type_Name".to_string(), "<{value}>".to_string())],
+        ..Options::default()
+    };
+    let entity = process_object_entity_with_options(&reference, object_entity, &templated)
+        .expect("failed to parse object entity");
+    assert_eq!(
+        entity.get("Type_Name (picklist)"),
+        Some(&Value::String("<Assignment>".to_string()))
+    );
+}
+
+/// Reference with a nested form whose child form contains it again.
+fn cyclic_reference() -> HashMap<String, ObjectAttribute> {
+    common::reference(
+        [
+            common::nested_form(
+                "019883f0-c110-7bc5-854e-26a7135a9ec1",
+                "Order",
+                &["019883f0-c110-7bc5-854e-26a7135a9ec2"],
+            ),
+            common::nested_form(
+                "019883f0-c110-7bc5-854e-26a7135a9ec2",
+                "Parts",
+                &["019883f0-c110-7bc5-854e-26a7135a9ec3"],
+            ),
+            common::nested_form(
+                "019883f0-c110-7bc5-854e-26a7135a9ec3",
+                "Assemblies",
+                &["019883f0-c110-7bc5-854e-26a7135a9ec2"],
+            ),
+        ],
+        [],
+    )
+}
+
+#[test]
+fn nested_form_cycles() {
+    let reference = cyclic_reference();
+
+    let rows = |text: &str, name: &str| text.lines().filter(|line| line.contains(name)).count();
+
+    let table = render_schema(&reference, &SchemaFilter::default(), SchemaFormat::Table).unwrap();
+    assert_eq!(rows(&table, "Parts"), 1);
+    assert_eq!(rows(&table, "Assemblies"), 1);
+
+    let schema = generate_json_schema(&reference, JsonSchemaTarget::Entity).to_string();
+    assert_eq!(schema.matches("\"title\":\"Parts\"").count(), 1);
+    assert_eq!(schema.matches("\"title\":\"Assemblies\"").count(), 1);
+
+    let typescript = generate_typescript(&reference, "Order");
+    assert!(typescript.contains("export interface AssembliesEntry {\n}"));
+
+    let rust = generate_rust(&reference, "Order");
+    assert!(rust.contains("pub struct AssembliesEntry {\n}"));
+    syn::parse_file(&rust).expect("generated code should parse");
+
+    let dictionary = render_dictionary(&reference, "Orders", DictionaryFormat::Markdown);
+    assert_eq!(rows(&dictionary, "| Order / Parts / Assemblies |"), 1);
+    assert_eq!(rows(&dictionary, "/ Assemblies / Parts"), 0);
+}
+
+#[test]
+fn render_nested_form_schema() {
+    let mut line_amount = common::attribute(
+        "019883f0-c110-7bc5-854e-26a7135a9ec8",
+        "currency",
+        "Line_Amount",
+    );
+    line_amount["attributes"]["label"] = "Line Amount".into();
+
+    let reference = common::reference(
+        [
+            common::nested_form(
+                "019883f0-c110-7bc5-854e-26a7135a9ec7",
+                "Lines",
+                &["019883f0-c110-7bc5-854e-26a7135a9ec8"],
+            ),
+            line_amount,
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec9", "string", "Title"),
+        ],
+        [],
+    );
+
+    let filter = SchemaFilter {
+        name: Some("amount".to_string()),
+        ..SchemaFilter::default()
+    };
+    let schema = render_schema(&reference, &filter, SchemaFormat::Json).unwrap();
+    let schema: Value = serde_json::from_str(&schema).unwrap();
+
+    assert_eq!(schema.as_array().unwrap().len(), 1);
+    assert_eq!(schema[0]["name"], "Lines");
+    assert_eq!(schema[0]["children"][0]["name"], "Line_Amount");
+
+    let filter = SchemaFilter {
+        data_type: Some("string".to_string()),
+        ..SchemaFilter::default()
+    };
+    let schema = render_schema(&reference, &filter, SchemaFormat::Markdown).unwrap();
+
+    assert!(schema.contains("| Title |"));
+    assert!(!schema.contains("Lines"));
+}
+
+#[test]
+fn lookup_by_id_fragment_and_name() {
+    let reference = common::setup();
+
+    let matches = lookup(&reference, "oa_019883f0_c110", 10);
+    assert_eq!(matches[0].kind, LookupKind::ObjectAttribute);
+    assert_eq!(matches[0].object_attribute_name, "Type_Name");
+
+    let matches = lookup(&reference, "d645-7d51", 10);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].kind, LookupKind::PicklistOption);
+    assert_eq!(matches[0].option_name.as_deref(), Some("Factoring"));
+
+    let matches = lookup(&reference, "the type field", 10);
+    assert_eq!(matches[0].object_attribute_name, "Type_Name");
+
+    assert!(lookup(&reference, "unrelated", 10).is_empty());
+}
+
+#[test]
+fn generate_json_schema_for_entity_and_payload() {
+    let reference = common::setup();
+
+    let schema = generate_json_schema(&reference, JsonSchemaTarget::Entity);
+    let property = &schema["properties"]["oa_019883f0_c110_7bc5_854e_26a7135a9ec0"];
+
+    assert_eq!(property["title"], "Type_Name");
+    assert_eq!(property["oneOf"].as_array().unwrap().len(), 3);
+    assert_eq!(property["oneOf"][2]["type"], "null");
+
+    let schema = generate_json_schema(&reference, JsonSchemaTarget::Payload);
+    let entity = &schema["properties"]["object_entity_attribute_values"];
+
+    assert_eq!(
+        entity["properties"]["oa_019883f0_c110_7bc5_854e_26a7135a9ec0"]["title"],
+        "Type_Name"
+    );
+
+    // Options without a label are described by their name.
+    let mut unlabelled = common::option("option-a", "Open");
+    unlabelled["attributes"]["label"] = "".into();
+    let reference = common::reference(
+        [common::picklist(
+            "019883f0-c110-7bc5-854e-26a7135a9ec2",
+            "Stage",
+            &["option-a"],
+        )],
+        [unlabelled],
+    );
+    let schema = generate_json_schema(&reference, JsonSchemaTarget::Entity);
+
+    assert_eq!(
+        schema["properties"]["oa_019883f0_c110_7bc5_854e_26a7135a9ec2"]["oneOf"][0]["description"],
+        "Open"
+    );
+}
+
+#[test]
+fn generate_typescript_interfaces() {
+    let reference = common::setup();
+
+    let typescript = generate_typescript(&reference, "order entity");
+
+    assert!(typescript.contains("export interface OrderEntity {"));
+    assert!(
+        typescript.contains("  oa_019883f0_c110_7bc5_854e_26a7135a9ec0?: TypeNameOption | null;")
+    );
+    assert!(typescript.contains("  Factoring: \"019883f0-d645-7d51-ad02-c252ae9ef8c3\","));
+    assert!(typescript.contains("  oa_019883f0_c110_7bc5_854e_26a7135a9ec0: \"Type_Name\";"));
+    assert!(typescript.contains("export type OrderEntityByName = {"));
+}
+
+#[test]
+fn generate_rust_types() {
+    let mut lines = common::nested_form(
+        "019883f0-c110-7bc5-854e-26a7135a9ec7",
+        "Lines",
+        &["019883f0-c110-7bc5-854e-26a7135a9ec8"],
+    );
+    lines["attributes"]["object_class_id"] = "0198aaaa-0000-7000-8000-000000000000".into();
+    let mut kind = common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec9", "string", "type");
+    kind["attributes"]["object_class_id"] = "0198bbbb-0000-7000-8000-000000000000".into();
+
+    let reference = common::reference(
+        [
+            lines,
+            common::attribute(
+                "019883f0-c110-7bc5-854e-26a7135a9ec8",
+                "currency",
+                "Line_Amount",
+            ),
+            kind,
+        ],
+        [],
+    );
+
+    let rust = generate_rust(&reference, "order");
+
+    assert!(rust.contains("pub struct Order0198aaaa {"));
+    assert!(rust.contains("pub struct Order0198bbbb {"));
+    assert!(rust.contains("pub lines: Option<HashMap<String, NestedFormEntry<LinesEntry>>>,"));
+    assert!(rust.contains("pub line_amount: Option<f64>,"));
+    assert!(rust.contains("pub r#type: Option<String>,"));
+    assert!(
+        rust.contains("pub const LINE_AMOUNT: &str = \"019883f0-c110-7bc5-854e-26a7135a9ec8\";")
+    );
+
+    syn::parse_file(&rust).expect("generated code should parse");
+
+    let rust = generate_rust(&common::setup(), "order");
+
+    assert!(rust.contains("pub struct Order {"));
+    assert!(rust.contains("pub type_name: Option<TypeNameOption>,"));
+    assert!(rust.contains(
+        "    #[serde(rename = \"019883f0-d645-7d15-a8d0-f53e3d1b6a5b\")]\n    Assignment,"
+    ));
+    syn::parse_file(&rust).expect("generated code should parse");
+
+    // Names without letters or digits and keywords that can't be raw identifiers.
+    let labels = ["Self", "self", "crate", "super", "***", "+"];
+    let reference = common::reference(
+        [
+            common::picklist("019883f0-c110-7bc5-854e-26a7135a9ec1", "self", &labels),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec2", "string", "%%%"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec3", "string", "type"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec4", "string", "?"),
+        ],
+        labels.map(|label| common::option(label, label)),
+    );
+
+    let rust = generate_rust(&reference, "self");
+
+    assert!(rust.contains("pub struct ObjectEntity {"));
+    assert!(rust.contains("pub self_: Option<SelfOption>,"));
+    assert!(rust.contains("    Variant1,"));
+    assert!(rust.contains("    Crate,"));
+    assert!(rust.contains("    Variant5,"));
+    assert!(rust.contains("pub field_1: Option<String>,"));
+    assert!(rust.contains("pub field_2: Option<String>,"));
+    assert!(rust.contains("pub r#type: Option<String>,"));
+    syn::parse_file(&rust).expect("generated code should parse");
+}
+
+#[test]
+fn render_data_dictionary() {
+    let reference = common::setup();
+
+    let markdown = render_dictionary(&reference, "Orders", DictionaryFormat::Markdown);
+
+    assert!(markdown.starts_with("# Orders\n"));
+    assert!(markdown.contains("- [Attributes](#attributes)"));
+    assert!(markdown.contains(
+        "| <a id=\"oa-019883f0-c110-7bc5-854e-26a7135a9ec0\"></a>Type Name | Type_Name |"
+    ));
+    assert!(markdown.contains("Assignment (`019883f0-d645-7d15-a8d0-f53e3d1b6a5b`)"));
+
+    let content = fs::read_to_string("tests/oa.json")
+        .unwrap()
+        .replace("\"Type Name\"", "\"<b>Type</b> Name\"");
+    let escaped = common::parse_reference(&content);
+    let markdown = render_dictionary(&escaped, "Orders", DictionaryFormat::Markdown);
+
+    assert!(markdown.contains("</a>&lt;b&gt;Type&lt;/b&gt; Name | Type_Name |"));
+
+    let html = render_dictionary(&reference, "Orders & co", DictionaryFormat::Html);
+
+    assert!(html.contains("<title>Orders &amp; co</title>"));
+    assert!(html.contains("<section id=\"attributes\">"));
+    assert!(html.contains("<tr id=\"oa-019883f0-c110-7bc5-854e-26a7135a9ec0\">"));
+}
+
+#[test]
+fn parse_object_entity_with_display_formatting() {
+    let reference = common::reference(
+        [
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea1", "date", "Due"),
+            common::attribute(
+                "019883f0-c110-7bc5-854e-26a7135a9ea2",
+                "datetime",
+                "Created",
+            ),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea3", "percent", "Rate"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea4", "currency", "Amount"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea5", "currency", "Fee"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea6", "boolean", "Paid"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ea7", "address", "Address"),
+        ],
+        [],
+    );
+
+    let entity = serde_json::json!({
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea1": "2025-08-08T10:00:00",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea2": "2025-08-08T10:03:05.123Z",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea3": 12.5,
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea4": 1234.5,
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea5": "1234.4",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea6": false,
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ea7": {
+            "street": "1 Main St",
+            "city": "Springfield",
+            "postal_code": "12345",
+            "country": "US",
+            "state": ""
+        }
+    });
+    let entity: HashMap<String, Value> = serde_json::from_value(entity).unwrap();
+    let options = Options {
+        key_style: KeyStyle::Name,
+        display: true,
+        currency: Some("JPY".to_string()),
+        ..Options::default()
+    };
+
+    let result = process_object_entity_with_options(&reference, entity.clone(), &options).unwrap();
+
+    assert_eq!(result["Due"], "2025-08-08");
+    assert_eq!(result["Created"], "2025-08-08 10:03:05 UTC");
+    assert_eq!(result["Rate"], "12.5%");
+    assert_eq!(result["Amount"], "1235 JPY");
+    assert_eq!(result["Fee"], "1234 JPY");
+    assert_eq!(result["Paid"], "no");
+    assert_eq!(result["Address"], "1 Main St, Springfield, 12345, US");
+
+    let options = Options {
+        key_style: KeyStyle::Name,
+        display: true,
+        currency: Some("kwd".to_string()),
+        ..Options::default()
+    };
+    let result = process_object_entity_with_options(&reference, entity.clone(), &options).unwrap();
+
+    assert_eq!(result["Amount"], "1234.500 KWD");
+    assert_eq!(result["Fee"], "1234.400 KWD");
+
+    let options = Options {
+        key_style: KeyStyle::Name,
+        ..Options::default()
+    };
+    let result = process_object_entity_with_options(&reference, entity, &options).unwrap();
+
+    assert_eq!(result["Amount"], 1234.5);
+    assert_eq!(result["Paid"], false);
+}
+
+#[test]
+fn parse_payload_with_timezone() {
+    let reference = common::reference(
+        [
+            common::attribute(
+                "019883f0-c110-7bc5-854e-26a7135a9eb1",
+                "datetime",
+                "Created",
+            ),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9eb2", "date", "Due"),
+        ],
+        [],
+    );
+
+    let payload = serde_json::json!({
+        "search_query": {
+            "search_query_groups": [{
+                "operator": "AND",
+                "search_query_conditions": [
+                    {
+                        "object_attribute_id": "019883f0-c110-7bc5-854e-26a7135a9eb1",
+                        "value": "2025-08-08T20:00:00Z",
+                        "operator": "greater"
+                    },
+                    {
+                        "object_attribute_id": "019883f0-c110-7bc5-854e-26a7135a9eb2",
+                        "operator": "today"
+                    }
+                ]
+            }]
+        },
+        "object_entity_attribute_values": {
+            "oa_019883f0_c110_7bc5_854e_26a7135a9eb1": "2025-08-08T20:00:00Z"
+        }
+    });
+    let options = Options {
+        key_style: KeyStyle::Name,
+        timezone: Some("+08:00".parse::<Timezone>().unwrap()),
+        keep_original: true,
+        ..Options::default()
+    };
+
+    let result = process_payload_with_options(
+        &reference,
+        serde_json::from_value(payload).unwrap(),
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(
+        result["object_entity_attribute_values"]["Created"],
+        serde_json::json!({
+            "value": "2025-08-09T04:00:00+08:00",
+            "original": "2025-08-08T20:00:00Z"
+        })
+    );
+
+    let search_query = result["search_query"].as_str().unwrap();
+
+    assert!(search_query.contains("(greater_than Created {\"original\":\"2025-08-08T20:00:00Z\",\"value\":\"2025-08-09T04:00:00+08:00\"})"));
+
+    // The range depends on the current date, its bounds are tested in timezone.rs.
+    let range = search_query
+        .split("(today Due ")
+        .nth(1)
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(range, _)| range)
+        .unwrap();
+    let (start, end) = range.split_once("..").unwrap();
+
+    assert!(start.parse::<jiff::civil::Date>().unwrap() < end.parse().unwrap());
+
+    assert!("Europe/Berlin".parse::<Timezone>().is_ok());
+    assert!("Mars/Olympus".parse::<Timezone>().is_err());
+
+    for offset in ["+0530", "-05:30", "UTC+2", "GMT-03:00"] {
+        assert!(offset.parse::<Timezone>().is_ok(), "{offset}");
+    }
+
+    for offset in ["+éa", "+1é", "-é:00", "+08:é", "++8", "+26", "+08:60", "+"] {
+        assert!(offset.parse::<Timezone>().is_err(), "{offset}");
+    }
+}
+
+struct InternalId;
+
+impl ValueFormatter for InternalId {
+    fn format(&self, _: &ObjectAttribute, value: &Value) -> Option<Value> {
+        let id = value.as_str()?.strip_prefix("INT-")?;
+        Some(format!("internal #{}", id.trim_start_matches('0')).into())
+    }
+}
+
+#[test]
+fn parse_payload_with_custom_formatters() {
+    let reference = common::reference(
+        [
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec1", "text", "Ref"),
+            common::attribute("019883f0-c110-7bc5-854e-26a7135a9ec2", "text", "Note"),
+        ],
+        [],
+    );
+
+    let mut options = Options {
+        key_style: KeyStyle::Name,
+        ..Options::default()
+    };
+    options
+        .register_formatter(
+            FormatterTarget::DataType(ObjectAttributeDataType::Text),
+            InternalId,
+        )
+        .register_formatter(
+            FormatterTarget::Attribute("oa_019883f0_c110_7bc5_854e_26a7135a9ec2".to_string()),
+            |attribute: &ObjectAttribute, value: &Value| {
+                Some(format!("{}: {}", attribute.label(), value.as_str()?).into())
+            },
+        );
+
+    let payload = serde_json::json!({
+        "search_query": {
+            "search_query_groups": [{
+                "operator": "AND",
+                "search_query_conditions": [{
+                    "object_attribute_id": "019883f0-c110-7bc5-854e-26a7135a9ec1",
+                    "value": "INT-0042",
+                    "operator": "equal"
+                }]
+            }]
+        },
+        "object_entity_attribute_values": {
+            "oa_019883f0_c110_7bc5_854e_26a7135a9ec1": "INT-0007",
+            "oa_019883f0_c110_7bc5_854e_26a7135a9ec2": "INT-0008"
+        }
+    });
+    let result = process_payload_with_options(
+        &reference,
+        serde_json::from_value(payload).unwrap(),
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(result["search_query"], "(AND (equal Ref \"internal #42\"))");
+    assert_eq!(
+        result["object_entity_attribute_values"]["Ref"],
+        "internal #7"
+    );
+    assert_eq!(
+        result["object_entity_attribute_values"]["Note"],
+        "Note: INT-0008"
+    );
+}
+
+#[test]
+fn translator_with_several_references() {
+    let customers = || {
+        common::reference(
+            [
+                common::attribute("019883f0-c110-7bc5-854e-26a7135a9ed2", "string", "Zeta"),
+                common::attribute("019883f0-c110-7bc5-854e-26a7135a9ed1", "string", "Alpha"),
+                common::attribute(
+                    "019883f0-c110-7bc5-854e-26a7135a9ec0",
+                    "string",
+                    "Duplicate",
+                ),
+            ],
+            [],
+        )
+    };
+
+    let translator = Translator::builder()
+        .reference(common::setup())
+        .reference(customers())
+        .key_style(KeyStyle::Name)
+        .order(AttributeOrder::Reference)
+        .redact("Alpha")
+        .build();
+
+    let entity: HashMap<String, Value> = serde_json::from_value(serde_json::json!({
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ed1": "a",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ed2": "z",
+        "oa_019883f0_c110_7bc5_854e_26a7135a9ec0": "019883f0-d645-7d51-ad02-c252ae9ef8c3"
+    }))
+    .unwrap();
+
+    let result = translator.entity(entity.clone()).unwrap();
+
+    assert_eq!(
+        result.keys().collect::<Vec<&str>>(),
+        ["Type_Name", "Zeta", "Alpha"]
+    );
+    assert_eq!(result["Type_Name"], "Factoring");
+    assert_eq!(result["Alpha"], "[redacted]");
+    assert_eq!(
+        serde_json::to_string(&result).unwrap(),
+        r#"{"Type_Name":"Factoring","Zeta":"z","Alpha":"[redacted]"}"#
+    );
+
+    let mut translator = Translator::new(common::setup());
+    translator.add_reference(customers());
+    translator.options_mut().key_style = KeyStyle::Name;
+    let result = translator.entity(entity.clone()).unwrap();
+
+    assert_eq!(
+        result.keys().collect::<Vec<&str>>(),
+        ["Alpha", "Type_Name", "Zeta"]
+    );
+
+    let payload: Payload = serde_json::from_value(serde_json::json!({
+        "object_entity_attribute_values": entity
+    }))
+    .unwrap();
+    let result = translator.payload(payload).unwrap();
+
+    assert!(result.search_query.is_none());
+    assert_eq!(result.object_entity_attribute_values.unwrap()["Zeta"], "z");
+
+    // Merged references keep the order of the translator.
+    let merged = merge_references([common::setup(), customers()]);
+    let translator = Translator::builder()
+        .reference(merged)
+        .key_style(KeyStyle::Name)
+        .order(AttributeOrder::Reference)
+        .build();
+    let result = translator.entity(entity).unwrap();
+
+    assert_eq!(
+        result.keys().collect::<Vec<&str>>(),
+        ["Type_Name", "Zeta", "Alpha"]
+    );
+}
+
+#[test]
+fn translator_with_borrowed_input() {
+    let translator = Translator::builder()
+        .reference(common::setup())
+        .order(AttributeOrder::Reference)
+        .build();
+
+    let payload_content = get_content("tests/payload.json".to_string());
+    let payload: Value = serde_json::from_str(&payload_content).unwrap();
+    let owned = translator
+        .payload(serde_json::from_value(payload.clone()).unwrap())
+        .unwrap();
+
+    assert_eq!(translator.payload_value(&payload).unwrap(), owned);
+    assert_eq!(translator.payload_str(&payload_content).unwrap(), owned);
+
+    let response_content = get_content("tests/response.json".to_string());
+    let response: Value = serde_json::from_str(&response_content).unwrap();
+    let owned = translator
+        .response(serde_json::from_value(response.clone()).unwrap())
+        .unwrap();
+
+    assert_eq!(translator.response_value(&response).unwrap(), owned);
+    assert_eq!(translator.response_str(&response_content).unwrap(), owned);
+
+    let entity_content = get_content("tests/object_entity.json".to_string());
+    let entity: Value = serde_json::from_str(&entity_content).unwrap();
+    let owned = translator
+        .entity(serde_json::from_value(entity.clone()).unwrap())
+        .unwrap();
+
+    assert_eq!(translator.entity_value(&entity).unwrap(), owned);
+    assert_eq!(translator.entity_str(&entity_content).unwrap(), owned);
+
+    assert!(translator.response_value(&entity).is_err());
+    assert!(translator.entity_str("[]").is_err());
+
+    // Keys borrow from the reference, not the input, and can be made owned.
+    let borrowed = translator.entity_value(&entity.clone()).unwrap();
+    assert_eq!(borrowed, owned);
+
+    let owned = borrowed.into_owned();
+    drop(translator);
+    assert_eq!(owned.len(), entity.as_object().unwrap().len());
+}
+
+#[test]
+fn translator_streams_response() {
+    let translator = Translator::new(common::setup());
+    let response_content = get_content("tests/response.json".to_string());
+    let entities: Vec<Value> = translator
+        .response_str(&response_content)
+        .unwrap()
+        .into_iter()
+        .map(Value::from)
+        .collect();
+
+    let mut ndjson = Vec::new();
+    let count = translator
+        .write_response(
+            response_content.as_bytes(),
+            &mut ndjson,
+            StreamFormat::Ndjson,
+        )
+        .unwrap();
+
+    assert_eq!(count, entities.len());
+    assert_eq!(
+        String::from_utf8(ndjson)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect::<Vec<Value>>(),
+        entities
+    );
+
+    let mut array = Vec::new();
+    translator
+        .write_response(response_content.as_bytes(), &mut array, StreamFormat::Array)
+        .unwrap();
+
+    assert_eq!(
+        serde_json::from_slice::<Value>(&array).unwrap(),
+        Value::from(entities)
+    );
+
+    let mut empty = Vec::new();
+    translator
+        .write_response(&b"{\"data\": []}"[..], &mut empty, StreamFormat::Array)
+        .unwrap();
+
+    assert_eq!(empty, b"[]\n");
+
+    let strict = Translator::builder()
+        .reference(common::setup())
+        .strict(true)
+        .build();
+    let error = strict
+        .response_stream(response_content.as_bytes(), |_| Ok(()))
+        .unwrap_err();
+
+    assert!(error.to_string().starts_with("Unknown object attribute id"));
+
+    // The array is closed when translation fails part-way.
+    let partial = r#"{"data": [
+        {"id": "a", "attributes": {"oa_019883f0_c110_7bc5_854e_26a7135a9ec0": null}},
+        {"id": "b", "attributes": {"oa_1": 2}}
+    ]}"#;
+    let mut array = Vec::new();
+
+    assert!(
+        strict
+            .write_response(partial.as_bytes(), &mut array, StreamFormat::Array)
+            .is_err()
+    );
+    assert_eq!(
+        serde_json::from_slice::<Value>(&array)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn translator_detects_input_kind() {
+    let translator = Translator::new(common::setup());
+    let read =
+        |path: &str| -> Value { serde_json::from_str(&get_content(path.to_string())).unwrap() };
+
+    for (path, kind) in [
+        ("tests/payload.json", InputKind::Payload),
+        ("tests/response.json", InputKind::Response),
+        ("tests/object_entity.json", InputKind::Entity),
+    ] {
+        let input = read(path);
+
+        assert_eq!(InputKind::detect(&input), Some(kind));
+        assert_eq!(
+            translator.translate_value(&input).unwrap(),
+            translator.translate_kind(kind, &input).unwrap()
+        );
+        assert_eq!(translator.translate_value(&input).unwrap().kind(), kind);
+    }
+
+    assert_eq!(InputKind::detect(&serde_json::json!({ "x": 1 })), None);
+    assert_eq!(InputKind::detect(&serde_json::json!([])), None);
+    assert!(
+        translator
+            .translate_value(&serde_json::json!({ "x": 1 }))
+            .is_err()
+    );
+}
+
+#[test]
+fn query_text_to_payload() {
+    let type_name = "019883f0-c110-7bc5-854e-26a7135a9ec0";
+    let factoring = "019883f0-d645-7d51-ad02-c252ae9ef8c3";
+    let assignment = "019883f0-d645-7d15-a8d0-f53e3d1b6a5b";
+    let payload = serde_json::json!({
+        "search_query": {
+            "search_query_groups": [{
+                "operator": "AND",
+                "search_query_conditions": [
+                    { "object_attribute_id": type_name, "operator": "any_of", "value": [factoring, assignment] },
+                    { "object_attribute_id": type_name, "operator": "is_present" }
+                ],
+                "children": [{
+                    "operator": "NOT",
+                    "search_query_conditions": [
+                        { "object_attribute_id": type_name, "operator": "equal", "value": factoring }
+                    ]
+                }]
+            }]
+        }
+    });
+
+    for key_style in [
+        KeyStyle::Name,
+        KeyStyle::NameType,
+        KeyStyle::Label,
+        KeyStyle::Id,
+        KeyStyle::LabelId,
+    ] {
+        let translator = Translator::builder()
+            .reference(common::setup())
+            .key_style(key_style)
+            .build();
+        let query = translator
+            .payload_value(&payload)
+            .unwrap()
+            .search_query
+            .unwrap();
+        let parsed = translator.query_payload(&query).unwrap();
+
+        assert_eq!(parsed, payload, "{query}");
+    }
+
+    let reference = common::setup();
+
+    assert_eq!(
+        query_to_payload(&reference, "(or (greater_than \"Type Name\" Factoring))").unwrap(),
+        serde_json::json!({
+            "search_query": {
+                "search_query_groups": [{
+                    "operator": "OR",
+                    "search_query_conditions": [
+                        { "object_attribute_id": type_name, "operator": "greater", "value": factoring }
+                    ]
+                }]
+            }
+        })
+    );
+    assert!(query_to_payload(&reference, "(AND (equal Missing 1))").is_err());
+    assert!(query_to_payload(&reference, "(AND (equal Type_Name Unknown))").is_err());
+    assert!(query_to_payload(&reference, "(AND (equal Type_Name 1)").is_err());
+    assert!(query_to_payload(&reference, "(AND (equal Type_Name (AND)))").is_err());
+
+    // Names with parentheses, as printed from a label.
+    let reference = common::reference(
+        [common::attribute(
+            "019883f0-c110-7bc5-854e-26a7135a9ec3",
+            "currency",
+            "Amount (EUR)",
+        )],
+        [],
+    );
+    let translator = Translator::builder()
+        .reference(reference)
+        .key_style(KeyStyle::Label)
+        .build();
+    let payload = serde_json::json!({
+        "search_query": {
+            "search_query_groups": [{
+                "operator": "AND",
+                "search_query_conditions": [
+                    { "object_attribute_id": "019883f0-c110-7bc5-854e-26a7135a9ec3", "operator": "equal", "value": 5 },
+                    { "object_attribute_id": "019883f0-c110-7bc5-854e-26a7135a9ec3", "operator": "is_present" }
+                ]
+            }]
+        }
+    });
+    let query = translator
+        .payload_value(&payload)
+        .unwrap()
+        .search_query
+        .unwrap();
+
+    assert_eq!(
+        query,
+        "(AND (equal Amount (EUR) 5) (is_present Amount (EUR)))"
+    );
+    assert_eq!(translator.query_payload(&query).unwrap(), payload);
+    assert!(
+        translator
+            .query_payload("(AND (equal Amount (EUR)))")
+            .is_err()
+    );
+}